@@ -0,0 +1,21 @@
+fn main() {
+    #[cfg(feature = "capi")]
+    {
+        let crate_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+        let out_dir = std::env::var("OUT_DIR").unwrap();
+        println!("cargo:rerun-if-changed=src/capi.rs");
+        println!("cargo:rerun-if-changed=cbindgen.toml");
+        println!("cargo:rerun-if-env-changed=MEDIAINFO_UPDATE_HEADER");
+
+        // Only OUT_DIR is ours to write; the checked-in header is refreshed on request, so packaging
+        // doesn't see the source tree change under it.
+        let header = format!("{}/mediainfo.h", out_dir);
+        cbindgen::generate(&crate_dir)
+            .expect("unable to generate the C header")
+            .write_to_file(&header);
+
+        if std::env::var_os("MEDIAINFO_UPDATE_HEADER").is_some() {
+            std::fs::copy(&header, format!("{}/include/mediainfo.h", crate_dir)).expect("unable to copy the C header");
+        }
+    }
+}
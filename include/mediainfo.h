@@ -0,0 +1,391 @@
+#ifndef MEDIAINFO_H
+#define MEDIAINFO_H
+
+/* Generated by cbindgen from src/capi.rs. Do not edit by hand. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * Status codes returned by every call; the first five match the Java `CommandResult` ordinals.
+ */
+typedef enum MediainfoError {
+  MEDIAINFO_ERROR_OK = 0,
+  MEDIAINFO_ERROR_NOT_SUPPORTED = 1,
+  MEDIAINFO_ERROR_NO_PLAYER = 2,
+  MEDIAINFO_ERROR_CAPABILITY_DISABLED = 3,
+  MEDIAINFO_ERROR_FAILED = 4,
+  MEDIAINFO_ERROR_INVALID_ARGUMENT = 5,
+} MediainfoError;
+
+/**
+ * Opaque handle naming one player, from `mediainfo_players` or `mediainfo_player_new`.
+ */
+typedef struct MediainfoPlayer MediainfoPlayer;
+
+/**
+ * Opaque handle. Keeps the message of the last failed call made through it, and the player its
+ * calls go to if one was set.
+ */
+typedef struct MediainfoSession MediainfoSession;
+
+typedef struct MediainfoMediaInfo {
+  char *title;
+  char *artist;
+  char *album;
+  char *album_art;
+} MediainfoMediaInfo;
+
+typedef struct MediainfoPlaybackState {
+  bool is_playing;
+  bool is_pausing;
+  bool is_stopped;
+  bool is_shuffling;
+  bool is_repeating_track;
+  bool is_repeating_playlist;
+  int64_t current_time;
+  int64_t max_time;
+  bool play_enabled;
+  bool pause_enabled;
+  bool stop_enabled;
+  bool record_enabled;
+  bool fast_forward_enabled;
+  bool rewind_enabled;
+  bool next_enabled;
+  bool previous_enabled;
+  bool channel_up_enabled;
+  bool channel_down_enabled;
+  bool play_pause_toggle_enabled;
+  bool shuffle_enabled;
+  bool repeat_enabled;
+  bool playback_rate_enabled;
+  bool playback_position_enabled;
+  double playback_rate;
+  double volume;
+} MediainfoPlaybackState;
+
+typedef struct MediainfoSnapshot {
+  int64_t generation;
+  struct MediainfoMediaInfo media_info;
+  struct MediainfoPlaybackState playback_state;
+} MediainfoSnapshot;
+
+typedef struct MediainfoTrack {
+  char *track_id;
+  struct MediainfoMediaInfo media_info;
+} MediainfoTrack;
+
+typedef struct MediainfoPlaylist {
+  char *playlist_id;
+  char *name;
+  char *icon;
+} MediainfoPlaylist;
+
+struct MediainfoSession *mediainfo_session_new(void);
+
+/**
+ * # Safety
+ * `session` must come from `mediainfo_session_new` and not be used afterwards. Null is ignored.
+ */
+void mediainfo_session_free(struct MediainfoSession *session);
+
+/**
+ * Message of the last `MEDIAINFO_ERROR_FAILED` on `session`, valid until the next call through it.
+ *
+ * # Safety
+ * `session` must be a live session handle.
+ */
+const char *mediainfo_last_error(const struct MediainfoSession *session);
+
+/**
+ * Writes up to `capacity` handles for the players the platform knows about to `players` and
+ * returns how many there are, which may be more. Free each handle with `mediainfo_player_free`.
+ *
+ * # Safety
+ * `players` must point to room for `capacity` handles, or be null with a `capacity` of 0.
+ */
+size_t mediainfo_players(struct MediainfoPlayer **players, size_t capacity);
+
+/**
+ * A handle for the player called `name`, matched like `--player` of the `mediainfo` command: the
+ * bus or app name, or the identity. It needn't be running yet; calls fail with
+ * `MEDIAINFO_ERROR_NO_PLAYER` while it isn't. Returns null if `name` isn't valid UTF-8.
+ *
+ * # Safety
+ * `name` must be a NUL-terminated string.
+ */
+struct MediainfoPlayer *mediainfo_player_new(const char *name);
+
+/**
+ * Bus or app name of `player`, valid as long as the handle.
+ *
+ * # Safety
+ * `player` must be a live player handle.
+ */
+const char *mediainfo_player_name(const struct MediainfoPlayer *player);
+
+/**
+ * Display identity of `player`, valid as long as the handle.
+ *
+ * # Safety
+ * `player` must be a live player handle.
+ */
+const char *mediainfo_player_identity(const struct MediainfoPlayer *player);
+
+/**
+ * # Safety
+ * `player` must come from this library and not be used afterwards. Null is ignored.
+ */
+void mediainfo_player_free(struct MediainfoPlayer *player);
+
+/**
+ * Sends every call, from every session and the snapshot and change tracking, to `player`, or back
+ * to the active player for null.
+ *
+ * # Safety
+ * `player` must be a live player handle, or null.
+ */
+void mediainfo_select_player(const struct MediainfoPlayer *player);
+
+/**
+ * Sends the calls made through `session` to `player`, or back to the selected or active player for
+ * null. The generation `mediainfo_wait_for_change` waits on keeps counting the selected player's changes.
+ *
+ * # Safety
+ * `session` must be a live session handle and `player` a live player handle, or null.
+ */
+enum MediainfoError mediainfo_session_set_player(struct MediainfoSession *session,
+                                                 const struct MediainfoPlayer *player);
+
+/**
+ * Fills `snapshot` with the player's state as of now, along with the current generation; free it
+ * with `mediainfo_snapshot_free`.
+ *
+ * # Safety
+ * `session` must be a live session handle, or null. `snapshot` must point to writable memory for a `MediainfoSnapshot`.
+ */
+enum MediainfoError mediainfo_get_snapshot(struct MediainfoSession *session,
+                                           struct MediainfoSnapshot *snapshot);
+
+/**
+ * Blocks until the snapshot generation differs from `generation` or `timeout_ms` passes, then
+ * fills `snapshot` like `mediainfo_get_snapshot`.
+ *
+ * # Safety
+ * `session` must be a live session handle, or null. `snapshot` must point to writable memory for a `MediainfoSnapshot`.
+ */
+enum MediainfoError mediainfo_wait_for_change(struct MediainfoSession *session,
+                                              int64_t generation,
+                                              uint64_t timeout_ms,
+                                              struct MediainfoSnapshot *snapshot);
+
+/**
+ * Releases the strings of a snapshot filled by this library. The struct itself stays the caller's.
+ *
+ * # Safety
+ * `snapshot` must have been filled by `mediainfo_get_snapshot` or `mediainfo_wait_for_change` and not freed yet.
+ */
+void mediainfo_snapshot_free(struct MediainfoSnapshot *snapshot);
+
+/**
+ * # Safety
+ * `string` must be a string handed out by this library, or null.
+ */
+void mediainfo_string_free(char *string);
+
+/**
+ * Writes the player's track list to `tracks` and its length to `count`. Free it with
+ * `mediainfo_track_list_free`.
+ *
+ * # Safety
+ * `session` must be a live session handle, `tracks` and `count` writable.
+ */
+enum MediainfoError mediainfo_get_track_list(struct MediainfoSession *session,
+                                             struct MediainfoTrack **tracks,
+                                             size_t *count);
+
+/**
+ * # Safety
+ * `tracks` and `count` must come from `mediainfo_get_track_list` and not be freed yet.
+ */
+void mediainfo_track_list_free(struct MediainfoTrack *tracks, size_t count);
+
+/**
+ * Writes a page of the player's playlists to `playlists` and its length to `count`, like
+ * `NativeMedia.getPlaylists`. `order` is one of the player's orderings, e.g. "Alphabetical". Free
+ * it with `mediainfo_playlist_list_free`.
+ *
+ * # Safety
+ * `session` must be a live session handle, `order` a NUL-terminated string, `playlists` and `count` writable.
+ */
+enum MediainfoError mediainfo_get_playlists(struct MediainfoSession *session,
+                                            uint32_t index,
+                                            uint32_t max_count,
+                                            const char *order,
+                                            bool reverse_order,
+                                            struct MediainfoPlaylist **playlists,
+                                            size_t *count);
+
+/**
+ * # Safety
+ * `playlists` and `count` must come from `mediainfo_get_playlists` and not be freed yet.
+ */
+void mediainfo_playlist_list_free(struct MediainfoPlaylist *playlists, size_t count);
+
+/**
+ * # Safety
+ * `session` must be a live session handle.
+ */
+enum MediainfoError mediainfo_play(struct MediainfoSession *session);
+
+/**
+ * # Safety
+ * `session` must be a live session handle.
+ */
+enum MediainfoError mediainfo_pause(struct MediainfoSession *session);
+
+/**
+ * # Safety
+ * `session` must be a live session handle.
+ */
+enum MediainfoError mediainfo_stop(struct MediainfoSession *session);
+
+/**
+ * # Safety
+ * `session` must be a live session handle.
+ */
+enum MediainfoError mediainfo_record(struct MediainfoSession *session);
+
+/**
+ * # Safety
+ * `session` must be a live session handle.
+ */
+enum MediainfoError mediainfo_fast_forward(struct MediainfoSession *session);
+
+/**
+ * # Safety
+ * `session` must be a live session handle.
+ */
+enum MediainfoError mediainfo_rewind(struct MediainfoSession *session);
+
+/**
+ * # Safety
+ * `session` must be a live session handle.
+ */
+enum MediainfoError mediainfo_toggle_play_pause(struct MediainfoSession *session);
+
+/**
+ * # Safety
+ * `session` must be a live session handle.
+ */
+enum MediainfoError mediainfo_next(struct MediainfoSession *session);
+
+/**
+ * # Safety
+ * `session` must be a live session handle.
+ */
+enum MediainfoError mediainfo_previous(struct MediainfoSession *session);
+
+/**
+ * # Safety
+ * `session` must be a live session handle.
+ */
+enum MediainfoError mediainfo_channel_up(struct MediainfoSession *session);
+
+/**
+ * # Safety
+ * `session` must be a live session handle.
+ */
+enum MediainfoError mediainfo_channel_down(struct MediainfoSession *session);
+
+/**
+ * # Safety
+ * `session` must be a live session handle.
+ */
+enum MediainfoError mediainfo_shuffle(struct MediainfoSession *session, bool shuffle);
+
+/**
+ * # Safety
+ * `session` must be a live session handle.
+ */
+enum MediainfoError mediainfo_repeat(struct MediainfoSession *session);
+
+/**
+ * # Safety
+ * `session` must be a live session handle.
+ */
+enum MediainfoError mediainfo_set_playback_rate(struct MediainfoSession *session, double rate);
+
+/**
+ * # Safety
+ * `session` must be a live session handle.
+ */
+enum MediainfoError mediainfo_set_playback_position(struct MediainfoSession *session,
+                                                    int64_t position);
+
+/**
+ * `volume` is linear, 0.0 muted and 1.0 full.
+ *
+ * # Safety
+ * `session` must be a live session handle.
+ */
+enum MediainfoError mediainfo_set_volume(struct MediainfoSession *session, double volume);
+
+/**
+ * # Safety
+ * `session` must be a live session handle and `uri` a NUL-terminated string.
+ */
+enum MediainfoError mediainfo_open_uri(struct MediainfoSession *session, const char *uri);
+
+/**
+ * # Safety
+ * `session` must be a live session handle.
+ */
+enum MediainfoError mediainfo_raise(struct MediainfoSession *session);
+
+/**
+ * # Safety
+ * `session` must be a live session handle.
+ */
+enum MediainfoError mediainfo_quit(struct MediainfoSession *session);
+
+/**
+ * # Safety
+ * `session` must be a live session handle.
+ */
+enum MediainfoError mediainfo_set_fullscreen(struct MediainfoSession *session, bool fullscreen);
+
+/**
+ * # Safety
+ * `session` must be a live session handle and `track_id` a NUL-terminated string.
+ */
+enum MediainfoError mediainfo_go_to(struct MediainfoSession *session, const char *track_id);
+
+/**
+ * Adds `uri` after `after_track_id`, or at the start of the list when it is null.
+ *
+ * # Safety
+ * `session` must be a live session handle, `uri` a NUL-terminated string and `after_track_id` one or null.
+ */
+enum MediainfoError mediainfo_add_track(struct MediainfoSession *session,
+                                        const char *uri,
+                                        const char *after_track_id,
+                                        bool set_as_current);
+
+/**
+ * # Safety
+ * `session` must be a live session handle and `track_id` a NUL-terminated string.
+ */
+enum MediainfoError mediainfo_remove_track(struct MediainfoSession *session, const char *track_id);
+
+/**
+ * # Safety
+ * `session` must be a live session handle and `playlist_id` a NUL-terminated string.
+ */
+enum MediainfoError mediainfo_activate_playlist(struct MediainfoSession *session,
+                                                const char *playlist_id);
+
+#endif  /* MEDIAINFO_H */
@@ -0,0 +1,356 @@
+//! `mediainfo`, a playerctl-like command line client going through the same backend as the JNI exports.
+
+#[cfg(unix)]
+use mediaInfo::rpc;
+use {
+    mediaInfo::{media, overlay::Overlay, server::Server, template::Template, CommandResult, Event, Snapshot},
+    std::{
+        env,
+        process::ExitCode,
+        sync::mpsc,
+        time::Duration
+    }
+};
+
+const USAGE: &str = "\
+Usage: mediainfo [OPTIONS] COMMAND [ARG]
+
+Commands:
+  status            Playback status of the player
+  metadata          Title, artist, album, art and length of the current track
+  players           Name and identity of every player
+  play | pause | play-pause | stop | next | previous
+  seek OFFSET       Seek to OFFSET seconds, or by it when prefixed with + or -
+  volume [LEVEL]    Print the volume, or set it to LEVEL (0.0-1.0), or change it by LEVEL with a + or - suffix
+  serve [ADDRESS]   Serve /snapshot, /art, /control/{command} and /events on ADDRESS (127.0.0.1:8974)
+  rpc [PATH]        Serve JSON-RPC on a Unix socket at PATH ($XDG_RUNTIME_DIR/mediainfo.sock)
+  overlay DIR       Keep now-playing.txt (from --format), album-art and now-playing.json in DIR up to date
+
+Options:
+  -p, --player NAME     Use the player NAME instead of the active one
+  -a, --all-players     Run the command on every player
+  -F, --follow          Keep printing status or metadata as it changes
+  -j, --json            Print status and metadata as JSON
+  -t, --token TOKEN     Require TOKEN from clients of serve, needed to serve beyond loopback
+  -f, --format TEMPLATE Print status and metadata through TEMPLATE, e.g.
+                        '{{#artist}}{{artist}} - {{/artist}}{{title}} ({{position|duration}}/{{length|duration}})'
+  -h, --help            Print this help";
+
+/// The default `metadata` output, one field per line.
+const METADATA: &str = "\
+title     {{title}}
+artist    {{artist}}
+album     {{album}}
+albumArt  {{albumArt}}
+length    {{length|duration}}";
+
+/// The default overlay text.
+const OVERLAY: &str = "{{#artist}}{{artist}} - {{/artist}}{{title}}";
+
+const SERVER_ADDRESS: &str = "127.0.0.1:8974";
+
+/// How long property changes are coalesced while following, so one track change prints once.
+const FOLLOW_WINDOW: Duration = Duration::from_millis(100);
+
+#[derive(Default)]
+struct Options {
+    player: Option<String>,
+    token: Option<String>,
+    all_players: bool,
+    follow: bool,
+    output: Output,
+    command: String,
+    argument: Option<String>,
+}
+
+#[derive(Default)]
+enum Output {
+    #[default]
+    Text,
+    Json,
+    Template(Template),
+}
+
+fn main() -> ExitCode {
+    if env::args().any(|arg| arg == "-h" || arg == "--help") {
+        println!("{}", USAGE);
+        return ExitCode::SUCCESS;
+    }
+
+    let options = match parse(env::args().skip(1)) {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("{}\n\n{}", message, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+
+    if let Some(player) = &options.player {
+        media::select_player(Some(player));
+    }
+
+    match options.command.as_str() {
+        "status" | "metadata" => print_snapshots(&options),
+        "players" => {
+            for (name, identity) in media::players() {
+                println!("{}\t{}", name, identity);
+            }
+            ExitCode::SUCCESS
+        }
+        "volume" if options.argument.is_none() => print_snapshots(&options),
+        "overlay" => run_overlay(&options),
+        "serve" => serve(&options),
+        #[cfg(unix)]
+        "rpc" => serve_rpc(&options),
+        _ => run_command(&options),
+    }
+}
+
+fn parse(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut options = Options::default();
+    let mut positional = Vec::new();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-p" | "--player" => options.player = Some(args.next().ok_or("--player needs a NAME")?),
+            "-t" | "--token" => options.token = Some(args.next().ok_or("--token needs a TOKEN")?),
+            "-a" | "--all-players" => options.all_players = true,
+            "-F" | "--follow" => options.follow = true,
+            "-j" | "--json" => options.output = Output::Json,
+            "-f" | "--format" => {
+                let template = args.next().ok_or("--format needs a TEMPLATE")?;
+                options.output = Output::Template(Template::parse(&template).map_err(|e| format!("invalid template: {}", e))?);
+            }
+            // Anything else starting with '-' that isn't a negative seek offset.
+            _ if arg.starts_with('-') && arg.parse::<f64>().is_err() => return Err(format!("unknown option {}", arg)),
+            _ => positional.push(arg),
+        }
+    }
+
+    let mut positional = positional.into_iter();
+    options.command = positional.next().ok_or("no command given")?;
+    options.argument = positional.next();
+
+    if let Some(extra) = positional.next() {
+        return Err(format!("unexpected argument {}", extra));
+    }
+    if options.player.is_some() && options.all_players {
+        return Err("--player and --all-players can't be combined".to_string());
+    }
+    Ok(options)
+}
+
+/// Prints the snapshot of the selected players, and again whenever the output would change when following.
+fn print_snapshots(options: &Options) -> ExitCode {
+    if media::players().is_empty() && !options.follow {
+        eprintln!("No players found");
+        return ExitCode::FAILURE;
+    }
+
+    let mut last = render_all(options);
+    print!("{}", last);
+    if !options.follow {
+        return ExitCode::SUCCESS;
+    }
+
+    let (sender, receiver) = mpsc::channel();
+    media::subscribe(FOLLOW_WINDOW, move |event| sender.send(event).is_ok());
+
+    for event in receiver {
+        if let Event::Changed(_) = event {
+            let output = render_all(options);
+            if output != last {
+                print!("{}", output);
+                last = output;
+            }
+        }
+    }
+    ExitCode::SUCCESS
+}
+
+fn render_all(options: &Options) -> String {
+    if !options.all_players {
+        return render(options, options.player.as_deref(), &media::snapshot());
+    }
+
+    let mut output = String::new();
+    for (name, _) in media::players() {
+        let snapshot = media::with_player(&name, media::snapshot);
+        output += &render(options, Some(&name), &snapshot);
+    }
+    output
+}
+
+fn render(options: &Options, player: Option<&str>, snapshot: &Snapshot) -> String {
+    let text = match &options.output {
+        Output::Json => {
+            let mut json = serde_json::to_value(snapshot).unwrap();
+            json["player"] = player.into();
+            json.to_string()
+        }
+        Output::Template(template) => template.render(snapshot, player),
+        Output::Text => {
+            let lines: Vec<String> = match options.command.as_str() {
+                "status" => vec![snapshot.playback_state().status().to_string()],
+                "volume" => vec![snapshot.playback_state().volume().to_string()],
+                _ => Template::parse(METADATA).unwrap().render(snapshot, player).lines().map(str::to_string).collect(),
+            };
+
+            // Tell the players apart the way playerctl does, by prefixing their name.
+            match (options.all_players, player) {
+                (true, Some(player)) => lines.iter().map(|line| format!("{} {}", player, line)).collect::<Vec<_>>().join("\n"),
+                _ => lines.join("\n"),
+            }
+        }
+    };
+    text + "\n"
+}
+
+/// Serves until interrupted.
+fn serve(options: &Options) -> ExitCode {
+    let mut server = Server::new(options.argument.as_deref().unwrap_or(SERVER_ADDRESS)).window(FOLLOW_WINDOW);
+    if let Some(token) = &options.token {
+        server = server.token(token);
+    }
+
+    if let Err(e) = server.start() {
+        eprintln!("{}", e);
+        return ExitCode::FAILURE;
+    }
+    loop {
+        std::thread::park();
+    }
+}
+
+/// Serves JSON-RPC until interrupted.
+#[cfg(unix)]
+fn serve_rpc(options: &Options) -> ExitCode {
+    let server = match &options.argument {
+        Some(path) => rpc::Server::new(path),
+        None => match rpc::Server::with_default_path() {
+            Some(server) => server,
+            None => {
+                eprintln!("XDG_RUNTIME_DIR isn't set, give the socket a PATH");
+                return ExitCode::FAILURE;
+            }
+        },
+    };
+
+    let path = server.path().display().to_string();
+    if let Err(e) = server.start() {
+        eprintln!("can't serve on {}: {}", path, e);
+        return ExitCode::FAILURE;
+    }
+    loop {
+        std::thread::park();
+    }
+}
+
+/// Writes the overlay files until interrupted.
+fn run_overlay(options: &Options) -> ExitCode {
+    let Some(directory) = &options.argument else {
+        eprintln!("overlay needs a DIR\n\n{}", USAGE);
+        return ExitCode::from(2);
+    };
+    let template = match &options.output {
+        Output::Template(template) => template.clone(),
+        _ => Template::parse(OVERLAY).unwrap(),
+    };
+
+    let overlay = Overlay::new(directory, template);
+    if let Err(e) = overlay.write(&media::snapshot()) {
+        eprintln!("can't write to {}: {}", directory, e);
+        return ExitCode::FAILURE;
+    }
+
+    overlay.start(FOLLOW_WINDOW);
+    loop {
+        std::thread::park();
+    }
+}
+
+/// Runs a control command on the selected player, or on every player with `--all-players`.
+fn run_command(options: &Options) -> ExitCode {
+    let command = match to_command(&options.command, options.argument.as_deref()) {
+        Ok(command) => command,
+        Err(message) => {
+            eprintln!("{}\n\n{}", message, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+
+    let succeeded = if !options.all_players {
+        report(None, command())
+    } else {
+        let players = media::players();
+        let mut succeeded = !players.is_empty() || report(None, CommandResult::NoPlayer);
+        for (name, _) in players {
+            succeeded &= report(Some(&name), media::with_player(&name, &command));
+        }
+        succeeded
+    };
+
+    if succeeded {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+fn to_command(name: &str, argument: Option<&str>) -> Result<Box<dyn Fn() -> CommandResult>, String> {
+    Ok(match (name, argument) {
+        ("play", None) => Box::new(media::play),
+        ("pause", None) => Box::new(media::pause),
+        ("play-pause", None) => Box::new(media::toggle_play_pause),
+        ("stop", None) => Box::new(media::stop),
+        ("next", None) => Box::new(media::next),
+        ("previous", None) => Box::new(media::previous),
+        ("seek", Some(offset)) => {
+            let seconds: f64 = offset.parse().map_err(|_| format!("invalid seek offset {}", offset))?;
+            if offset.starts_with(['+', '-']) {
+                Box::new(move || media::seek(seconds))
+            } else {
+                Box::new(move || media::set_playback_position((seconds * media::time_units_per_second() as f64) as i64))
+            }
+        }
+        ("volume", Some(level)) => {
+            let (value, sign) = match level.strip_suffix('+') {
+                Some(value) => (value, 1.0),
+                None => match level.strip_suffix('-') {
+                    Some(value) => (value, -1.0),
+                    None => (level, 0.0),
+                },
+            };
+            let value: f64 = value.parse().map_err(|_| format!("invalid volume {}", level))?;
+
+            if sign == 0.0 {
+                Box::new(move || media::set_volume(value))
+            } else {
+                Box::new(move || match media::playback_state() {
+                    Ok(state) if state.volume() >= 0.0 => media::set_volume((state.volume() + sign * value).clamp(0.0, 1.0)),
+                    Ok(_) => CommandResult::NotSupported,
+                    Err(_) => CommandResult::NoPlayer,
+                })
+            }
+        }
+        ("seek", None) => return Err("seek needs an OFFSET".to_string()),
+        _ => return Err(format!("unknown command {}", name)),
+    })
+}
+
+/// Prints why a command didn't go through, prefixed with the player when there are several.
+fn report(player: Option<&str>, result: CommandResult) -> bool {
+    let message = match result {
+        CommandResult::Ok => return true,
+        CommandResult::NotSupported => "Not supported by this player".to_string(),
+        CommandResult::NoPlayer => "No players found".to_string(),
+        CommandResult::CapabilityDisabled => "The player doesn't allow this right now".to_string(),
+        CommandResult::Failed(message) => message,
+    };
+
+    match player {
+        Some(player) => eprintln!("{}: {}", player, message),
+        None => eprintln!("{}", message),
+    }
+    false
+}
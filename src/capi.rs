@@ -0,0 +1,651 @@
+//! `extern "C"` API over [`crate::media`], for C, C++ and other FFI consumers. The build script
+//! generates the header into `OUT_DIR`; the checked-in `include/mediainfo.h` is refreshed from it by
+//! building with `MEDIAINFO_UPDATE_HEADER=1`.
+//!
+//! Calls go to the active player, or to the one picked with `mediainfo_select_player` for the
+//! whole process, or `mediainfo_session_set_player` for one session's calls only. Strings and
+//! arrays handed out are owned by the caller and released with the matching `*_free` function.
+
+use {
+    crate::{generation, media, CommandResult, MediaInfo, PlaybackState, PlaylistInfo, Snapshot, TrackInfo},
+    std::{
+        ffi::{c_char, CStr, CString},
+        ptr,
+        time::Duration
+    }
+};
+
+/// Status codes returned by every call; the first five match the Java `CommandResult` ordinals.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MediainfoError {
+    Ok = 0,
+    NotSupported = 1,
+    NoPlayer = 2,
+    CapabilityDisabled = 3,
+    Failed = 4,
+    InvalidArgument = 5,
+}
+
+/// Opaque handle. Keeps the message of the last failed call made through it, and the player its
+/// calls go to if one was set.
+pub struct MediainfoSession {
+    last_error: CString,
+    player: Option<String>,
+}
+
+/// Opaque handle naming one player, from `mediainfo_players` or `mediainfo_player_new`.
+pub struct MediainfoPlayer {
+    name: CString,
+    identity: CString,
+}
+
+#[repr(C)]
+pub struct MediainfoMediaInfo {
+    pub title: *mut c_char,
+    pub artist: *mut c_char,
+    pub album: *mut c_char,
+    pub album_art: *mut c_char,
+}
+
+#[repr(C)]
+pub struct MediainfoPlaybackState {
+    pub is_playing: bool,
+    pub is_pausing: bool,
+    pub is_stopped: bool,
+    pub is_shuffling: bool,
+    pub is_repeating_track: bool,
+    pub is_repeating_playlist: bool,
+    pub current_time: i64,
+    pub max_time: i64,
+    pub play_enabled: bool,
+    pub pause_enabled: bool,
+    pub stop_enabled: bool,
+    pub record_enabled: bool,
+    pub fast_forward_enabled: bool,
+    pub rewind_enabled: bool,
+    pub next_enabled: bool,
+    pub previous_enabled: bool,
+    pub channel_up_enabled: bool,
+    pub channel_down_enabled: bool,
+    pub play_pause_toggle_enabled: bool,
+    pub shuffle_enabled: bool,
+    pub repeat_enabled: bool,
+    pub playback_rate_enabled: bool,
+    pub playback_position_enabled: bool,
+    pub playback_rate: f64,
+    pub volume: f64,
+}
+
+#[repr(C)]
+pub struct MediainfoSnapshot {
+    pub generation: i64,
+    pub media_info: MediainfoMediaInfo,
+    pub playback_state: MediainfoPlaybackState,
+}
+
+#[repr(C)]
+pub struct MediainfoTrack {
+    pub track_id: *mut c_char,
+    pub media_info: MediainfoMediaInfo,
+}
+
+#[repr(C)]
+pub struct MediainfoPlaylist {
+    pub playlist_id: *mut c_char,
+    pub name: *mut c_char,
+    pub icon: *mut c_char,
+}
+
+#[no_mangle]
+pub extern "C" fn mediainfo_session_new() -> *mut MediainfoSession {
+    Box::into_raw(Box::new(MediainfoSession {
+        last_error: CString::default(),
+        player: None,
+    }))
+}
+
+/// # Safety
+/// `session` must come from `mediainfo_session_new` and not be used afterwards. Null is ignored.
+#[no_mangle]
+pub unsafe extern "C" fn mediainfo_session_free(session: *mut MediainfoSession) {
+    if !session.is_null() {
+        drop(Box::from_raw(session));
+    }
+}
+
+/// Message of the last `MEDIAINFO_ERROR_FAILED` on `session`, valid until the next call through it.
+///
+/// # Safety
+/// `session` must be a live session handle.
+#[no_mangle]
+pub unsafe extern "C" fn mediainfo_last_error(session: *const MediainfoSession) -> *const c_char {
+    match session.as_ref() {
+        Some(session) => session.last_error.as_ptr(),
+        None => ptr::null(),
+    }
+}
+
+/// Writes up to `capacity` handles for the players the platform knows about to `players` and
+/// returns how many there are, which may be more. Free each handle with `mediainfo_player_free`.
+///
+/// # Safety
+/// `players` must point to room for `capacity` handles, or be null with a `capacity` of 0.
+#[no_mangle]
+pub unsafe extern "C" fn mediainfo_players(players: *mut *mut MediainfoPlayer, capacity: usize) -> usize {
+    let all = media::players();
+    if !players.is_null() {
+        for (i, (name, identity)) in all.iter().take(capacity).enumerate() {
+            players.add(i).write(new_player(name, identity));
+        }
+    }
+    all.len()
+}
+
+/// A handle for the player called `name`, matched like `--player` of the `mediainfo` command: the
+/// bus or app name, or the identity. It needn't be running yet; calls fail with
+/// `MEDIAINFO_ERROR_NO_PLAYER` while it isn't. Returns null if `name` isn't valid UTF-8.
+///
+/// # Safety
+/// `name` must be a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn mediainfo_player_new(name: *const c_char) -> *mut MediainfoPlayer {
+    let Some(name) = to_str(name) else {
+        return ptr::null_mut();
+    };
+
+    let identity = media::players().into_iter().find(|(n, _)| n == name).map_or(name.to_string(), |(_, identity)| identity);
+    new_player(name, &identity)
+}
+
+/// Bus or app name of `player`, valid as long as the handle.
+///
+/// # Safety
+/// `player` must be a live player handle.
+#[no_mangle]
+pub unsafe extern "C" fn mediainfo_player_name(player: *const MediainfoPlayer) -> *const c_char {
+    player.as_ref().map_or(ptr::null(), |player| player.name.as_ptr())
+}
+
+/// Display identity of `player`, valid as long as the handle.
+///
+/// # Safety
+/// `player` must be a live player handle.
+#[no_mangle]
+pub unsafe extern "C" fn mediainfo_player_identity(player: *const MediainfoPlayer) -> *const c_char {
+    player.as_ref().map_or(ptr::null(), |player| player.identity.as_ptr())
+}
+
+/// # Safety
+/// `player` must come from this library and not be used afterwards. Null is ignored.
+#[no_mangle]
+pub unsafe extern "C" fn mediainfo_player_free(player: *mut MediainfoPlayer) {
+    if !player.is_null() {
+        drop(Box::from_raw(player));
+    }
+}
+
+/// Sends every call, from every session and the snapshot and change tracking, to `player`, or back
+/// to the active player for null.
+///
+/// # Safety
+/// `player` must be a live player handle, or null.
+#[no_mangle]
+pub unsafe extern "C" fn mediainfo_select_player(player: *const MediainfoPlayer) {
+    media::select_player(player_name(player).as_deref());
+}
+
+/// Sends the calls made through `session` to `player`, or back to the selected or active player for
+/// null. The generation `mediainfo_wait_for_change` waits on keeps counting the selected player's changes.
+///
+/// # Safety
+/// `session` must be a live session handle and `player` a live player handle, or null.
+#[no_mangle]
+pub unsafe extern "C" fn mediainfo_session_set_player(session: *mut MediainfoSession, player: *const MediainfoPlayer) -> MediainfoError {
+    match session.as_mut() {
+        Some(session) => {
+            session.player = player_name(player);
+            MediainfoError::Ok
+        }
+        None => MediainfoError::InvalidArgument,
+    }
+}
+
+/// Fills `snapshot` with the player's state as of now, along with the current generation; free it
+/// with `mediainfo_snapshot_free`.
+///
+/// # Safety
+/// `session` must be a live session handle, or null. `snapshot` must point to writable memory for a `MediainfoSnapshot`.
+#[no_mangle]
+pub unsafe extern "C" fn mediainfo_get_snapshot(session: *mut MediainfoSession, snapshot: *mut MediainfoSnapshot) -> MediainfoError {
+    if snapshot.is_null() {
+        return MediainfoError::InvalidArgument;
+    }
+
+    let (generation, _) = generation::current();
+    snapshot.write(to_c_snapshot(generation, on_player(session, media::snapshot)));
+    MediainfoError::Ok
+}
+
+/// Blocks until the snapshot generation differs from `generation` or `timeout_ms` passes, then
+/// fills `snapshot` like `mediainfo_get_snapshot`.
+///
+/// # Safety
+/// `session` must be a live session handle, or null. `snapshot` must point to writable memory for a `MediainfoSnapshot`.
+#[no_mangle]
+pub unsafe extern "C" fn mediainfo_wait_for_change(session: *mut MediainfoSession, generation: i64, timeout_ms: u64, snapshot: *mut MediainfoSnapshot) -> MediainfoError {
+    if snapshot.is_null() {
+        return MediainfoError::InvalidArgument;
+    }
+
+    let (generation, _) = generation::wait_for_change(generation, Duration::from_millis(timeout_ms));
+    snapshot.write(to_c_snapshot(generation, on_player(session, media::snapshot)));
+    MediainfoError::Ok
+}
+
+/// Releases the strings of a snapshot filled by this library. The struct itself stays the caller's.
+///
+/// # Safety
+/// `snapshot` must have been filled by `mediainfo_get_snapshot` or `mediainfo_wait_for_change` and not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn mediainfo_snapshot_free(snapshot: *mut MediainfoSnapshot) {
+    let Some(snapshot) = snapshot.as_mut() else {
+        return;
+    };
+
+    free_media_info(&mut snapshot.media_info);
+}
+
+/// # Safety
+/// `string` must be a string handed out by this library, or null.
+#[no_mangle]
+pub unsafe extern "C" fn mediainfo_string_free(string: *mut c_char) {
+    if !string.is_null() {
+        drop(CString::from_raw(string));
+    }
+}
+
+/// Writes the player's track list to `tracks` and its length to `count`. Free it with
+/// `mediainfo_track_list_free`.
+///
+/// # Safety
+/// `session` must be a live session handle, `tracks` and `count` writable.
+#[no_mangle]
+pub unsafe extern "C" fn mediainfo_get_track_list(session: *mut MediainfoSession, tracks: *mut *mut MediainfoTrack, count: *mut usize) -> MediainfoError {
+    if tracks.is_null() || count.is_null() {
+        return MediainfoError::InvalidArgument;
+    }
+
+    let list: Vec<MediainfoTrack> = on_player(session, media::track_list).iter().map(to_c_track).collect();
+    count.write(list.len());
+    tracks.write(into_c_array(list));
+    MediainfoError::Ok
+}
+
+/// # Safety
+/// `tracks` and `count` must come from `mediainfo_get_track_list` and not be freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn mediainfo_track_list_free(tracks: *mut MediainfoTrack, count: usize) {
+    for mut track in from_c_array(tracks, count) {
+        mediainfo_string_free(track.track_id);
+        free_media_info(&mut track.media_info);
+    }
+}
+
+/// Writes a page of the player's playlists to `playlists` and its length to `count`, like
+/// `NativeMedia.getPlaylists`. `order` is one of the player's orderings, e.g. "Alphabetical". Free
+/// it with `mediainfo_playlist_list_free`.
+///
+/// # Safety
+/// `session` must be a live session handle, `order` a NUL-terminated string, `playlists` and `count` writable.
+#[no_mangle]
+pub unsafe extern "C" fn mediainfo_get_playlists(
+    session: *mut MediainfoSession,
+    index: u32,
+    max_count: u32,
+    order: *const c_char,
+    reverse_order: bool,
+    playlists: *mut *mut MediainfoPlaylist,
+    count: *mut usize,
+) -> MediainfoError {
+    let Some(order) = to_str(order) else {
+        return MediainfoError::InvalidArgument;
+    };
+    if playlists.is_null() || count.is_null() {
+        return MediainfoError::InvalidArgument;
+    }
+
+    let list: Vec<MediainfoPlaylist> = on_player(session, || media::playlists(index, max_count, order, reverse_order))
+        .iter()
+        .map(to_c_playlist)
+        .collect();
+    count.write(list.len());
+    playlists.write(into_c_array(list));
+    MediainfoError::Ok
+}
+
+/// # Safety
+/// `playlists` and `count` must come from `mediainfo_get_playlists` and not be freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn mediainfo_playlist_list_free(playlists: *mut MediainfoPlaylist, count: usize) {
+    for playlist in from_c_array(playlists, count) {
+        mediainfo_string_free(playlist.playlist_id);
+        mediainfo_string_free(playlist.name);
+        mediainfo_string_free(playlist.icon);
+    }
+}
+
+/// # Safety
+/// `session` must be a live session handle.
+#[no_mangle]
+pub unsafe extern "C" fn mediainfo_play(session: *mut MediainfoSession) -> MediainfoError {
+    command(session, media::play)
+}
+
+/// # Safety
+/// `session` must be a live session handle.
+#[no_mangle]
+pub unsafe extern "C" fn mediainfo_pause(session: *mut MediainfoSession) -> MediainfoError {
+    command(session, media::pause)
+}
+
+/// # Safety
+/// `session` must be a live session handle.
+#[no_mangle]
+pub unsafe extern "C" fn mediainfo_stop(session: *mut MediainfoSession) -> MediainfoError {
+    command(session, media::stop)
+}
+
+/// # Safety
+/// `session` must be a live session handle.
+#[no_mangle]
+pub unsafe extern "C" fn mediainfo_record(session: *mut MediainfoSession) -> MediainfoError {
+    command(session, media::record)
+}
+
+/// # Safety
+/// `session` must be a live session handle.
+#[no_mangle]
+pub unsafe extern "C" fn mediainfo_fast_forward(session: *mut MediainfoSession) -> MediainfoError {
+    command(session, media::fast_forward)
+}
+
+/// # Safety
+/// `session` must be a live session handle.
+#[no_mangle]
+pub unsafe extern "C" fn mediainfo_rewind(session: *mut MediainfoSession) -> MediainfoError {
+    command(session, media::rewind)
+}
+
+/// # Safety
+/// `session` must be a live session handle.
+#[no_mangle]
+pub unsafe extern "C" fn mediainfo_toggle_play_pause(session: *mut MediainfoSession) -> MediainfoError {
+    command(session, media::toggle_play_pause)
+}
+
+/// # Safety
+/// `session` must be a live session handle.
+#[no_mangle]
+pub unsafe extern "C" fn mediainfo_next(session: *mut MediainfoSession) -> MediainfoError {
+    command(session, media::next)
+}
+
+/// # Safety
+/// `session` must be a live session handle.
+#[no_mangle]
+pub unsafe extern "C" fn mediainfo_previous(session: *mut MediainfoSession) -> MediainfoError {
+    command(session, media::previous)
+}
+
+/// # Safety
+/// `session` must be a live session handle.
+#[no_mangle]
+pub unsafe extern "C" fn mediainfo_channel_up(session: *mut MediainfoSession) -> MediainfoError {
+    command(session, media::channel_up)
+}
+
+/// # Safety
+/// `session` must be a live session handle.
+#[no_mangle]
+pub unsafe extern "C" fn mediainfo_channel_down(session: *mut MediainfoSession) -> MediainfoError {
+    command(session, media::channel_down)
+}
+
+/// # Safety
+/// `session` must be a live session handle.
+#[no_mangle]
+pub unsafe extern "C" fn mediainfo_shuffle(session: *mut MediainfoSession, shuffle: bool) -> MediainfoError {
+    command(session, || media::shuffle(shuffle))
+}
+
+/// # Safety
+/// `session` must be a live session handle.
+#[no_mangle]
+pub unsafe extern "C" fn mediainfo_repeat(session: *mut MediainfoSession) -> MediainfoError {
+    command(session, media::repeat)
+}
+
+/// # Safety
+/// `session` must be a live session handle.
+#[no_mangle]
+pub unsafe extern "C" fn mediainfo_set_playback_rate(session: *mut MediainfoSession, rate: f64) -> MediainfoError {
+    command(session, || media::set_playback_rate(rate))
+}
+
+/// # Safety
+/// `session` must be a live session handle.
+#[no_mangle]
+pub unsafe extern "C" fn mediainfo_set_playback_position(session: *mut MediainfoSession, position: i64) -> MediainfoError {
+    command(session, || media::set_playback_position(position))
+}
+
+/// `volume` is linear, 0.0 muted and 1.0 full.
+///
+/// # Safety
+/// `session` must be a live session handle.
+#[no_mangle]
+pub unsafe extern "C" fn mediainfo_set_volume(session: *mut MediainfoSession, volume: f64) -> MediainfoError {
+    command(session, || media::set_volume(volume))
+}
+
+/// # Safety
+/// `session` must be a live session handle and `uri` a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn mediainfo_open_uri(session: *mut MediainfoSession, uri: *const c_char) -> MediainfoError {
+    match to_str(uri) {
+        Some(uri) => command(session, || media::open_uri(uri)),
+        None => MediainfoError::InvalidArgument,
+    }
+}
+
+/// # Safety
+/// `session` must be a live session handle.
+#[no_mangle]
+pub unsafe extern "C" fn mediainfo_raise(session: *mut MediainfoSession) -> MediainfoError {
+    command(session, media::raise)
+}
+
+/// # Safety
+/// `session` must be a live session handle.
+#[no_mangle]
+pub unsafe extern "C" fn mediainfo_quit(session: *mut MediainfoSession) -> MediainfoError {
+    command(session, media::quit)
+}
+
+/// # Safety
+/// `session` must be a live session handle.
+#[no_mangle]
+pub unsafe extern "C" fn mediainfo_set_fullscreen(session: *mut MediainfoSession, fullscreen: bool) -> MediainfoError {
+    command(session, || media::set_fullscreen(fullscreen))
+}
+
+/// # Safety
+/// `session` must be a live session handle and `track_id` a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn mediainfo_go_to(session: *mut MediainfoSession, track_id: *const c_char) -> MediainfoError {
+    match to_str(track_id) {
+        Some(track_id) => command(session, || media::go_to(track_id)),
+        None => MediainfoError::InvalidArgument,
+    }
+}
+
+/// Adds `uri` after `after_track_id`, or at the start of the list when it is null.
+///
+/// # Safety
+/// `session` must be a live session handle, `uri` a NUL-terminated string and `after_track_id` one or null.
+#[no_mangle]
+pub unsafe extern "C" fn mediainfo_add_track(session: *mut MediainfoSession, uri: *const c_char, after_track_id: *const c_char, set_as_current: bool) -> MediainfoError {
+    let after_track_id = to_str(after_track_id);
+    match to_str(uri) {
+        Some(uri) => command(session, || media::add_track(uri, after_track_id, set_as_current)),
+        None => MediainfoError::InvalidArgument,
+    }
+}
+
+/// # Safety
+/// `session` must be a live session handle and `track_id` a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn mediainfo_remove_track(session: *mut MediainfoSession, track_id: *const c_char) -> MediainfoError {
+    match to_str(track_id) {
+        Some(track_id) => command(session, || media::remove_track(track_id)),
+        None => MediainfoError::InvalidArgument,
+    }
+}
+
+/// # Safety
+/// `session` must be a live session handle and `playlist_id` a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn mediainfo_activate_playlist(session: *mut MediainfoSession, playlist_id: *const c_char) -> MediainfoError {
+    match to_str(playlist_id) {
+        Some(playlist_id) => command(session, || media::activate_playlist(playlist_id)),
+        None => MediainfoError::InvalidArgument,
+    }
+}
+
+/// Runs `f` against the session's player, if it has one.
+unsafe fn on_player<T>(session: *const MediainfoSession, f: impl FnOnce() -> T) -> T {
+    match session.as_ref().and_then(|session| session.player.as_deref()) {
+        Some(player) => media::with_player(player, f),
+        None => f(),
+    }
+}
+
+unsafe fn command(session: *mut MediainfoSession, action: impl FnOnce() -> CommandResult) -> MediainfoError {
+    match on_player(session, action) {
+        CommandResult::Ok => MediainfoError::Ok,
+        CommandResult::NotSupported => MediainfoError::NotSupported,
+        CommandResult::NoPlayer => MediainfoError::NoPlayer,
+        CommandResult::CapabilityDisabled => MediainfoError::CapabilityDisabled,
+        CommandResult::Failed(message) => {
+            if let Some(session) = session.as_mut() {
+                session.last_error = to_c_string(message);
+            }
+            MediainfoError::Failed
+        }
+    }
+}
+
+unsafe fn to_str<'a>(string: *const c_char) -> Option<&'a str> {
+    if string.is_null() {
+        return None;
+    }
+    CStr::from_ptr(string).to_str().ok()
+}
+
+/// Interior NULs can't cross the boundary, so they are dropped rather than failing the whole call.
+fn to_c_string(string: String) -> CString {
+    CString::new(string.replace('\0', "")).unwrap_or_default()
+}
+
+fn new_player(name: &str, identity: &str) -> *mut MediainfoPlayer {
+    Box::into_raw(Box::new(MediainfoPlayer {
+        name: to_c_string(name.to_string()),
+        identity: to_c_string(identity.to_string()),
+    }))
+}
+
+unsafe fn player_name(player: *const MediainfoPlayer) -> Option<String> {
+    player.as_ref().map(|player| player.name.to_string_lossy().into_owned())
+}
+
+/// Hands `items` over as a pointer to the first, to be taken back with [`from_c_array`].
+fn into_c_array<T>(items: Vec<T>) -> *mut T {
+    Box::into_raw(items.into_boxed_slice()) as *mut T
+}
+
+unsafe fn from_c_array<T>(items: *mut T, count: usize) -> Vec<T> {
+    if items.is_null() {
+        return Vec::new();
+    }
+    Box::from_raw(ptr::slice_from_raw_parts_mut(items, count)).into_vec()
+}
+
+unsafe fn free_media_info(media_info: &mut MediainfoMediaInfo) {
+    for string in [&mut media_info.title, &mut media_info.artist, &mut media_info.album, &mut media_info.album_art] {
+        mediainfo_string_free(*string);
+        *string = ptr::null_mut();
+    }
+}
+
+fn to_c_snapshot(generation: i64, snapshot: Snapshot) -> MediainfoSnapshot {
+    MediainfoSnapshot {
+        generation,
+        media_info: to_c_media_info(snapshot.media_info()),
+        playback_state: to_c_playback_state(snapshot.playback_state()),
+    }
+}
+
+fn to_c_media_info(media_info: &MediaInfo) -> MediainfoMediaInfo {
+    MediainfoMediaInfo {
+        title: to_c_string(media_info.title().to_string()).into_raw(),
+        artist: to_c_string(media_info.artist().to_string()).into_raw(),
+        album: to_c_string(media_info.album().to_string()).into_raw(),
+        album_art: to_c_string(media_info.album_art().to_string()).into_raw(),
+    }
+}
+
+fn to_c_track(track: &TrackInfo) -> MediainfoTrack {
+    MediainfoTrack {
+        track_id: to_c_string(track.track_id().to_string()).into_raw(),
+        media_info: to_c_media_info(track.media_info()),
+    }
+}
+
+fn to_c_playlist(playlist: &PlaylistInfo) -> MediainfoPlaylist {
+    MediainfoPlaylist {
+        playlist_id: to_c_string(playlist.playlist_id().to_string()).into_raw(),
+        name: to_c_string(playlist.name().to_string()).into_raw(),
+        icon: to_c_string(playlist.icon().to_string()).into_raw(),
+    }
+}
+
+fn to_c_playback_state(state: &PlaybackState) -> MediainfoPlaybackState {
+    MediainfoPlaybackState {
+            is_playing: state.is_playing(),
+            is_pausing: state.is_pausing(),
+            is_stopped: state.is_stopped(),
+            is_shuffling: state.is_shuffling(),
+            is_repeating_track: state.is_repeating_track(),
+            is_repeating_playlist: state.is_repeating_playlist(),
+            current_time: state.current_time(),
+            max_time: state.max_time(),
+            play_enabled: state.play_enabled(),
+            pause_enabled: state.pause_enabled(),
+            stop_enabled: state.stop_enabled(),
+            record_enabled: state.record_enabled(),
+            fast_forward_enabled: state.fast_forward_enabled(),
+            rewind_enabled: state.rewind_enabled(),
+            next_enabled: state.next_enabled(),
+            previous_enabled: state.previous_enabled(),
+            channel_up_enabled: state.channel_up_enabled(),
+            channel_down_enabled: state.channel_down_enabled(),
+            play_pause_toggle_enabled: state.play_pause_toggle_enabled(),
+            shuffle_enabled: state.shuffle_enabled(),
+            repeat_enabled: state.repeat_enabled(),
+            playback_rate_enabled: state.playback_rate_enabled(),
+            playback_position_enabled: state.playback_position_enabled(),
+            playback_rate: state.playback_rate(),
+            volume: state.volume(),
+    }
+}
@@ -0,0 +1,57 @@
+//! Commands by their `NativeController` name, for the servers that receive them as text.
+
+use {
+    crate::{media, CommandResult},
+    std::fmt
+};
+
+pub(crate) enum Error {
+    UnknownCommand(String),
+    InvalidValue(String),
+}
+
+/// Runs `command` with its argument, if it takes one. `seek` moves by `value` seconds and `volume`
+/// is linear from 0.0 to 1.0; the rest take what their `NativeController` method does.
+pub(crate) fn run(command: &str, value: Option<&str>) -> Result<CommandResult, Error> {
+    Ok(match (command, value) {
+        ("play", _) => media::play(),
+        ("pause", _) => media::pause(),
+        ("stop", _) => media::stop(),
+        ("record", _) => media::record(),
+        ("fastForward", _) => media::fast_forward(),
+        ("rewind", _) => media::rewind(),
+        ("next", _) => media::next(),
+        ("previous", _) => media::previous(),
+        ("channelUp", _) => media::channel_up(),
+        ("channelDown", _) => media::channel_down(),
+        ("togglePlayPause", _) => media::toggle_play_pause(),
+        ("shuffle", value) => media::shuffle(parse(value)?),
+        ("repeat", _) => media::repeat(),
+        ("playbackRate", value) => media::set_playback_rate(parse(value)?),
+        ("playbackPosition", value) => media::set_playback_position(parse(value)?),
+        ("seek", value) => media::seek(parse(value)?),
+        ("volume", value) => media::set_volume(parse(value)?),
+        ("openUri", Some(uri)) => media::open_uri(uri),
+        ("raise", _) => media::raise(),
+        ("quit", _) => media::quit(),
+        ("fullscreen", value) => media::set_fullscreen(parse(value)?),
+        ("goTo", Some(track_id)) => media::go_to(track_id),
+        ("activatePlaylist", Some(playlist_id)) => media::activate_playlist(playlist_id),
+        ("openUri" | "goTo" | "activatePlaylist", None) => return Err(Error::InvalidValue(format!("{} needs a value", command))),
+        _ => return Err(Error::UnknownCommand(command.to_string())),
+    })
+}
+
+fn parse<T: std::str::FromStr>(value: Option<&str>) -> Result<T, Error> {
+    let value = value.ok_or(Error::InvalidValue("missing value".to_string()))?;
+    value.trim().parse().map_err(|_| Error::InvalidValue(format!("invalid value {}", value)))
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::UnknownCommand(command) => write!(f, "unknown command {}", command),
+            Error::InvalidValue(message) => f.write_str(message),
+        }
+    }
+}
@@ -0,0 +1,354 @@
+use {
+    crate::{MediaInfo, PlaylistInfo, Snapshot, TrackInfo},
+    std::{
+        fmt,
+        sync::{
+            atomic::{AtomicBool, AtomicU64, Ordering},
+            mpsc,
+            Mutex
+        },
+        thread::{self, JoinHandle},
+        time::{Duration, Instant}
+    }
+};
+
+/// Receives every event; returning `false` unsubscribes it.
+type Subscriber = Box<dyn FnMut(&Event) -> bool + Send>;
+
+static SUBSCRIBERS: Mutex<Vec<Subscriber>> = Mutex::new(Vec::new());
+static SOURCES: AtomicBool = AtomicBool::new(false);
+
+/// Bumped by [`stop`]; threads started under an older epoch wind down on their next wakeup.
+static EPOCH: AtomicU64 = AtomicU64::new(0);
+
+/// Every background thread, joined on shutdown so none outlives an unloaded library.
+static THREADS: Mutex<Vec<JoinHandle<()>>> = Mutex::new(Vec::new());
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum Event {
+    TrackListReplaced(Vec<String>, String),
+    TrackAdded(TrackInfo, String),
+    TrackRemoved(String),
+    PlaylistChanged(PlaylistInfo),
+    Seeked(i64),
+    PlayerAdded(String, String),
+    PlayerRemoved(String, String),
+    /// A player property changed. Never delivered as is; listeners get a coalesced `Changed` instead.
+    PropertiesChanged,
+    Changed(Snapshot),
+    TrackChanged(MediaInfo, MediaInfo),
+    PlaybackStatusChanged(&'static str, &'static str),
+    ShuffleChanged(bool, bool),
+    RepeatChanged(&'static str, &'static str),
+    RateChanged(f64, f64),
+    VolumeChanged(f64, f64),
+    CapabilitiesChanged(Vec<&'static str>, Vec<&'static str>),
+    /// A position trigger fired: the trigger id and the position it fired at.
+    PositionReached(i64, i64),
+    /// Interpolated position, sent to listeners that asked for progress ticks.
+    Progress(i64),
+}
+
+/// Coalesces a burst of property changes into one snapshot, sent `window` after the first change.
+pub(crate) struct Window {
+    pub(crate) window: Duration,
+    pending: Option<Instant>,
+}
+
+impl Window {
+    pub(crate) fn new(window: Duration) -> Self {
+        Self {
+            window,
+            pending: None,
+        }
+    }
+
+    pub(crate) fn touch(&mut self, now: Instant) {
+        self.pending.get_or_insert(now);
+    }
+
+    /// `None` when nothing is pending, or when the window is too long to ever end.
+    pub(crate) fn deadline(&self) -> Option<Instant> {
+        self.pending.and_then(|first| first.checked_add(self.window))
+    }
+
+    pub(crate) fn take_if_due(&mut self, now: Instant) -> bool {
+        if self.deadline().is_some_and(|deadline| deadline <= now) {
+            self.pending = None;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// Feeds every event from the signal watcher and the poller to `subscriber`, starting them on first use.
+pub(crate) fn subscribe(subscriber: impl FnMut(&Event) -> bool + Send + 'static) {
+    SUBSCRIBERS.lock().unwrap().push(Box::new(subscriber));
+    start();
+}
+
+/// Starts the signal watcher and the poller unless they are already running.
+pub(crate) fn start() {
+    if SOURCES.swap(true, Ordering::SeqCst) {
+        return;
+    }
+
+    let (sender, receiver) = mpsc::channel::<Event>();
+    spawn(move || {
+        // Ends once the sources have wound down and dropped their senders.
+        for event in receiver {
+            emit(event);
+        }
+    });
+
+    #[cfg(target_os = "windows")]
+    {
+        crate::poller::spawn(sender);
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        crate::poller::spawn(sender.clone());
+        crate::platform::linux::spawn_signal_watcher(sender);
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        // Not polled: the getters still panic without a now playing item, which the poller would
+        // hit as soon as nothing plays.
+        drop(sender);
+    }
+}
+
+pub(crate) fn epoch() -> u64 {
+    EPOCH.load(Ordering::SeqCst)
+}
+
+pub(crate) fn running(epoch: u64) -> bool {
+    EPOCH.load(Ordering::SeqCst) == epoch
+}
+
+pub(crate) fn spawn(f: impl FnOnce() + Send + 'static) {
+    let handle = thread::spawn(f);
+    let mut threads = THREADS.lock().unwrap();
+    threads.retain(|t| !t.is_finished());
+    threads.push(handle);
+}
+
+/// Drops every subscriber and tells the background threads to stop. Threads parked on a condition
+/// of their own are woken by their module's `shutdown`, then [`join`] waits for all of them.
+pub(crate) fn stop() {
+    EPOCH.fetch_add(1, Ordering::SeqCst);
+    SUBSCRIBERS.lock().unwrap().clear();
+}
+
+pub(crate) fn join() {
+    let threads = std::mem::take(&mut *THREADS.lock().unwrap());
+    for thread in threads {
+        // Shutting down from a listener callback must not wait on its own dispatcher.
+        if thread.thread().id() != thread::current().id() {
+            let _ = thread.join();
+        }
+    }
+    SOURCES.store(false, Ordering::SeqCst);
+}
+
+/// Like [`subscribe`], but bursts of property changes arrive as the typed `*Changed` events and a
+/// `Changed` snapshot once `window` has passed, the way Java listeners see them. The first event is
+/// the current snapshot. Stops once `send` returns `false`.
+pub(crate) fn subscribe_coalesced(window: Duration, mut send: impl FnMut(Event) -> bool + Send + 'static) {
+    /// How long the coalescing thread waits when no change is pending.
+    const IDLE_TIMEOUT: Duration = Duration::from_secs(60);
+
+    let (forward, changes) = mpsc::channel();
+    subscribe(move |event| forward.send(event.clone()).is_ok());
+
+    spawn(move || {
+        let mut window = Window::new(window);
+        let mut last = Snapshot::take();
+        if !send(Event::Changed(last.clone())) {
+            return;
+        }
+
+        loop {
+            let timeout = window.deadline().map_or(IDLE_TIMEOUT, |d| d.saturating_duration_since(Instant::now()));
+
+            match changes.recv_timeout(timeout) {
+                Ok(Event::PropertiesChanged) => window.touch(Instant::now()),
+                Ok(event) => {
+                    if !send(event) {
+                        return;
+                    }
+                }
+                Err(mpsc::RecvTimeoutError::Timeout) => {}
+                Err(mpsc::RecvTimeoutError::Disconnected) => return,
+            }
+
+            if window.take_if_due(Instant::now()) {
+                let snapshot = Snapshot::take();
+                let mut events = Event::diff(&last, &snapshot);
+                events.push(Event::Changed(snapshot.clone()));
+                last = snapshot;
+
+                if !events.into_iter().all(&mut send) {
+                    return;
+                }
+            }
+        }
+    });
+}
+
+/// Hands an event produced on the native side straight to the subscribers. Must not be called from one.
+pub(crate) fn emit(event: Event) {
+    SUBSCRIBERS.lock().unwrap().retain_mut(|s| s(&event));
+}
+
+impl Event {
+    /// Typed events for what differs between two consecutive snapshots, each carrying the old and new value.
+    pub(crate) fn diff(old: &Snapshot, new: &Snapshot) -> Vec<Event> {
+        let mut events = Vec::new();
+        let (old_state, new_state) = (&old.playback_state, &new.playback_state);
+
+        if old.media_info != new.media_info {
+            events.push(Event::TrackChanged(old.media_info.clone(), new.media_info.clone()));
+        }
+        if old_state.status() != new_state.status() {
+            events.push(Event::PlaybackStatusChanged(old_state.status(), new_state.status()));
+        }
+        if old_state.is_shuffling != new_state.is_shuffling {
+            events.push(Event::ShuffleChanged(old_state.is_shuffling, new_state.is_shuffling));
+        }
+        if old_state.repeat() != new_state.repeat() {
+            events.push(Event::RepeatChanged(old_state.repeat(), new_state.repeat()));
+        }
+        if old_state.playback_rate != new_state.playback_rate {
+            events.push(Event::RateChanged(old_state.playback_rate, new_state.playback_rate));
+        }
+        if old_state.volume != new_state.volume {
+            events.push(Event::VolumeChanged(old_state.volume, new_state.volume));
+        }
+        if old_state.capabilities() != new_state.capabilities() {
+            events.push(Event::CapabilitiesChanged(old_state.capabilities(), new_state.capabilities()));
+        }
+
+        events
+    }
+
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            Event::TrackListReplaced(..) => "TrackListReplaced",
+            Event::TrackAdded(..) => "TrackAdded",
+            Event::TrackRemoved(..) => "TrackRemoved",
+            Event::PlaylistChanged(..) => "PlaylistChanged",
+            Event::Seeked(..) => "Seeked",
+            Event::PlayerAdded(..) => "PlayerAdded",
+            Event::PlayerRemoved(..) => "PlayerRemoved",
+            Event::PropertiesChanged => "PropertiesChanged",
+            Event::Changed(..) => "Changed",
+            Event::TrackChanged(..) => "TrackChanged",
+            Event::PlaybackStatusChanged(..) => "PlaybackStatusChanged",
+            Event::ShuffleChanged(..) => "ShuffleChanged",
+            Event::RepeatChanged(..) => "RepeatChanged",
+            Event::RateChanged(..) => "RateChanged",
+            Event::VolumeChanged(..) => "VolumeChanged",
+            Event::CapabilitiesChanged(..) => "CapabilitiesChanged",
+            Event::PositionReached(..) => "PositionReached",
+            Event::Progress(..) => "Progress",
+        }
+    }
+}
+
+/// Typed `*Changed` events put the old value on the first line and the new one on the second.
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Event::TrackListReplaced(track_ids, current_track) => {
+                write!(f, "{},{}", current_track, track_ids.join(","))
+            }
+            Event::TrackAdded(track, after_track) => write!(f, "{},{}", after_track, track),
+            Event::TrackRemoved(track_id) => f.write_str(track_id),
+            Event::PlaylistChanged(playlist) => write!(f, "{}", playlist),
+            Event::Seeked(position) => write!(f, "{}", position),
+            Event::PlayerAdded(bus_name, identity) | Event::PlayerRemoved(bus_name, identity) => {
+                write!(f, "{},{}", bus_name, identity)
+            }
+            Event::PropertiesChanged => Ok(()),
+            Event::Changed(snapshot) => write!(f, "{}", snapshot),
+            Event::TrackChanged(old, new) => write!(f, "{}\n{}", old.clone().to_string(), new.clone().to_string()),
+            Event::PlaybackStatusChanged(old, new) | Event::RepeatChanged(old, new) => write!(f, "{}\n{}", old, new),
+            Event::ShuffleChanged(old, new) => write!(f, "{}\n{}", old, new),
+            Event::RateChanged(old, new) | Event::VolumeChanged(old, new) => write!(f, "{}\n{}", old, new),
+            Event::CapabilitiesChanged(old, new) => write!(f, "{}\n{}", old.join(","), new.join(",")),
+            Event::PositionReached(id, position) => write!(f, "{},{}", id, position),
+            Event::Progress(position) => write!(f, "{}", position),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::event::{Event, Window};
+    use crate::{MediaInfo, PlaybackState, Snapshot};
+    use std::time::{Duration, Instant};
+
+    fn snapshot(title: &str, playing: bool) -> Snapshot {
+        let mut playback_state = PlaybackState::empty();
+        playback_state.is_playing = playing;
+        playback_state.is_pausing = !playing;
+        playback_state.play_enabled = true;
+
+        let media_info = MediaInfo::new(title.to_string(), "Artist".to_string(), "Album".to_string(), "".to_string());
+        Snapshot::new(media_info, playback_state)
+    }
+
+    #[test]
+    fn identical_snapshots_have_no_diff() {
+        assert!(Event::diff(&snapshot("A", true), &snapshot("A", true)).is_empty());
+    }
+
+    #[test]
+    fn diff_reports_old_and_new_values() {
+        let events = Event::diff(&snapshot("A", true), &snapshot("B", false));
+        let events = events.into_iter().map(|e| (e.name(), e.to_string())).collect::<Vec<_>>();
+
+        assert_eq!(events, vec![
+            ("TrackChanged", "A,Artist,Album,\nB,Artist,Album,".to_string()),
+            ("PlaybackStatusChanged", "Playing\nPaused".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn window_coalesces_burst() {
+        let start = Instant::now();
+        let mut window = Window::new(Duration::from_millis(200));
+
+        window.touch(start);
+        window.touch(start + Duration::from_millis(50));
+        window.touch(start + Duration::from_millis(150));
+        assert!(!window.take_if_due(start + Duration::from_millis(199)));
+        assert!(window.take_if_due(start + Duration::from_millis(200)));
+        assert!(!window.take_if_due(start + Duration::from_millis(400)));
+    }
+
+    #[test]
+    fn zero_window_is_due_immediately() {
+        let start = Instant::now();
+        let mut window = Window::new(Duration::ZERO);
+
+        assert_eq!(window.deadline(), None);
+        window.touch(start);
+        assert!(window.take_if_due(start));
+    }
+
+    #[test]
+    fn endless_window_never_comes_due() {
+        let start = Instant::now();
+        let mut window = Window::new(Duration::MAX);
+
+        window.touch(start);
+        assert_eq!(window.deadline(), None);
+        assert!(!window.take_if_due(start + Duration::from_secs(3600)));
+    }
+}
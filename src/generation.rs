@@ -0,0 +1,98 @@
+use {
+    crate::{
+        event::{self, Event},
+        Snapshot
+    },
+    std::{
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            mpsc,
+            Condvar,
+            Mutex
+        },
+        time::{Duration, Instant}
+    }
+};
+
+/// The latest snapshot and a counter bumped every time it changes, for long-polling Java callers.
+struct Current {
+    generation: i64,
+    snapshot: Option<Snapshot>,
+}
+
+static CURRENT: Mutex<Current> = Mutex::new(Current {
+    generation: 0,
+    snapshot: None,
+});
+static CHANGED: Condvar = Condvar::new();
+static TRACKER: AtomicBool = AtomicBool::new(false);
+
+pub(crate) fn current() -> (i64, Snapshot) {
+    start();
+
+    let current = CURRENT.lock().unwrap();
+    (current.generation, current.snapshot.clone().unwrap_or_else(Snapshot::take))
+}
+
+/// Blocks until the generation is no longer `generation` or `timeout` passes, then returns the latest
+/// one. A timeout too long to have a deadline waits for the change however long it takes, or until
+/// shutdown.
+pub(crate) fn wait_for_change(generation: i64, timeout: Duration) -> (i64, Snapshot) {
+    start();
+
+    let deadline = Instant::now().checked_add(timeout);
+    let mut current = CURRENT.lock().unwrap();
+    while current.generation == generation && TRACKER.load(Ordering::SeqCst) {
+        current = match deadline {
+            Some(deadline) => {
+                let now = Instant::now();
+                if now >= deadline {
+                    break;
+                }
+                CHANGED.wait_timeout(current, deadline - now).unwrap().0
+            }
+            None => CHANGED.wait(current).unwrap(),
+        };
+    }
+
+    (current.generation, current.snapshot.clone().unwrap_or_else(Snapshot::take))
+}
+
+/// Forgets the tracked snapshot; the tracking thread ends with its subscription in `event::stop`.
+pub(crate) fn shutdown() {
+    TRACKER.store(false, Ordering::SeqCst);
+    CURRENT.lock().unwrap().snapshot = None;
+    CHANGED.notify_all();
+}
+
+fn start() {
+    if TRACKER.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    CURRENT.lock().unwrap().snapshot = Some(Snapshot::take());
+
+    let (sender, receiver) = mpsc::channel();
+    event::subscribe(move |event| match event {
+        Event::PropertiesChanged => sender.send(false).is_ok(),
+        Event::Seeked(_) => sender.send(true).is_ok(),
+        _ => true,
+    });
+
+    event::spawn(move || {
+        while let Ok(mut seeked) = receiver.recv() {
+            // Drain the rest of the burst so it costs one snapshot.
+            while let Ok(s) = receiver.try_recv() {
+                seeked |= s;
+            }
+
+            let snapshot = Snapshot::take();
+            let mut current = CURRENT.lock().unwrap();
+            // The position moves on its own, so it only counts as a change when the player seeked.
+            if seeked || !current.snapshot.as_ref().is_some_and(|last| last.same_state(&snapshot)) {
+                current.generation += 1;
+                CHANGED.notify_all();
+            }
+            current.snapshot = Some(snapshot);
+        }
+    });
+}
@@ -1,17 +1,89 @@
-use jni::objects::JClass;
-use jni::sys::{jboolean, jdouble, jlong, jstring, JNI_TRUE};
-use jni::JNIEnv;
+use jni::objects::{JClass, JObject, JString};
+use jni::sys::{jboolean, jdouble, jint, jlong, jobjectArray, jstring, JNI_TRUE, JNI_VERSION_1_8};
+use jni::{JNIEnv, JavaVM};
+use std::ffi::c_void;
+use std::cell::RefCell;
+use std::fmt;
+use std::time::Duration;
+
+thread_local! {
+    /// Message of the last `Failed` command on this thread, for `NativeController.getLastError`.
+    static LAST_ERROR: RefCell<String> = const { RefCell::new(String::new()) };
+}
 
+#[cfg(feature = "capi")]
+pub mod capi;
+#[cfg(any(feature = "server", all(unix, feature = "rpc")))]
+mod control;
+mod event;
+mod generation;
+mod listener;
+pub mod media;
+mod natives;
+#[cfg(feature = "overlay")]
+pub mod overlay;
 mod platform;
-
-struct MediaInfo {
+mod poller;
+mod position;
+#[cfg(feature = "python")]
+mod python;
+#[cfg(all(unix, feature = "rpc"))]
+pub mod rpc;
+#[cfg(feature = "server")]
+pub mod server;
+pub mod template;
+mod trigger;
+#[cfg(all(feature = "stream", target_os = "linux"))]
+pub mod stream;
+
+pub use event::Event;
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MediaInfo {
     title: String,
     artist: String,
     album: String,
     album_art: String,
 }
 
-struct PlaybackState {
+/// Media info and playback state read together, as delivered to change listeners.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Snapshot {
+    media_info: MediaInfo,
+    playback_state: PlaybackState,
+}
+
+/// Outcome of a `NativeController` command. Java sees it as the status code from [`CommandResult::code`].
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CommandResult {
+    Ok,
+    NotSupported,
+    NoPlayer,
+    CapabilityDisabled,
+    Failed(String),
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TrackInfo {
+    track_id: String,
+    media_info: MediaInfo,
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PlaylistInfo {
+    playlist_id: String,
+    name: String,
+    icon: String,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PlaybackState {
     is_playing: bool,
     is_pausing: bool,
     is_stopped: bool,
@@ -35,9 +107,27 @@ struct PlaybackState {
     repeat_enabled: bool,
     playback_rate_enabled: bool,
     playback_position_enabled: bool,
+    playback_rate: f64,
+    volume: f64,
 }
 
 impl MediaInfo {
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    pub fn artist(&self) -> &str {
+        &self.artist
+    }
+
+    pub fn album(&self) -> &str {
+        &self.album
+    }
+
+    pub fn album_art(&self) -> &str {
+        &self.album_art
+    }
+
     fn new(
         title: String,
         artist: String,
@@ -72,7 +162,230 @@ impl MediaInfo {
     }
 }
 
+impl Snapshot {
+    pub fn media_info(&self) -> &MediaInfo {
+        &self.media_info
+    }
+
+    pub fn playback_state(&self) -> &PlaybackState {
+        &self.playback_state
+    }
+
+    fn new(media_info: MediaInfo, playback_state: PlaybackState) -> Self {
+        Self {
+            media_info,
+            playback_state,
+        }
+    }
+
+    fn take() -> Self {
+        #[cfg(target_os = "windows")]
+        {
+            Self::new(platform::windows::get_media_info(), platform::windows::get_playback_state())
+        }
+
+        #[cfg(target_os = "linux")]
+        {
+            Self::new(
+                platform::linux::get_media_info().unwrap_or(MediaInfo::empty()),
+                platform::linux::get_playback_state().unwrap_or(PlaybackState::empty()),
+            )
+        }
+
+        #[cfg(target_os = "macos")]
+        {
+            Self::new(platform::macos::get_media_info(), platform::macos::get_playback_state())
+        }
+    }
+
+    /// Compares everything but the playback position, which moves on its own while playing.
+    fn same_state(&self, other: &Snapshot) -> bool {
+        let mut playback_state = other.playback_state.clone();
+        playback_state.current_time = self.playback_state.current_time;
+
+        self.media_info == other.media_info && self.playback_state == playback_state
+    }
+}
+
+impl fmt::Display for Snapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}\n{}", self.media_info.clone().to_string(), self.playback_state.clone().to_string())
+    }
+}
+
+impl CommandResult {
+    /// Matches the ordinal of the Java `CommandResult` enum.
+    fn code(&self) -> jint {
+        match self {
+            CommandResult::Ok => 0,
+            CommandResult::NotSupported => 1,
+            CommandResult::NoPlayer => 2,
+            CommandResult::CapabilityDisabled => 3,
+            CommandResult::Failed(_) => 4,
+        }
+    }
+}
+
+impl TrackInfo {
+    pub fn track_id(&self) -> &str {
+        &self.track_id
+    }
+
+    pub fn media_info(&self) -> &MediaInfo {
+        &self.media_info
+    }
+
+    fn new(track_id: String, media_info: MediaInfo) -> Self {
+        Self {
+            track_id,
+            media_info,
+        }
+    }
+}
+
+impl fmt::Display for TrackInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{}", self.track_id, self.media_info.clone().to_string())
+    }
+}
+
+impl PlaylistInfo {
+    pub fn playlist_id(&self) -> &str {
+        &self.playlist_id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn icon(&self) -> &str {
+        &self.icon
+    }
+
+    fn new(playlist_id: String, name: String, icon: String) -> Self {
+        Self {
+            playlist_id,
+            name,
+            icon,
+        }
+    }
+
+    fn empty() -> Self {
+        Self {
+            playlist_id: "Unavailable".to_string(),
+            name: "Unavailable".to_string(),
+            icon: "Unavailable".to_string(),
+        }
+    }
+}
+
+impl fmt::Display for PlaylistInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{},{}", self.playlist_id, self.name, self.icon)
+    }
+}
+
 impl PlaybackState {
+    pub fn is_playing(&self) -> bool {
+        self.is_playing
+    }
+
+    pub fn is_pausing(&self) -> bool {
+        self.is_pausing
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        self.is_stopped
+    }
+
+    pub fn is_shuffling(&self) -> bool {
+        self.is_shuffling
+    }
+
+    pub fn is_repeating_track(&self) -> bool {
+        self.is_repeating_track
+    }
+
+    pub fn is_repeating_playlist(&self) -> bool {
+        self.is_repeating_playlist
+    }
+
+    pub fn current_time(&self) -> i64 {
+        self.current_time
+    }
+
+    pub fn max_time(&self) -> i64 {
+        self.max_time
+    }
+
+    pub fn play_enabled(&self) -> bool {
+        self.play_enabled
+    }
+
+    pub fn pause_enabled(&self) -> bool {
+        self.pause_enabled
+    }
+
+    pub fn stop_enabled(&self) -> bool {
+        self.stop_enabled
+    }
+
+    pub fn record_enabled(&self) -> bool {
+        self.record_enabled
+    }
+
+    pub fn fast_forward_enabled(&self) -> bool {
+        self.fast_forward_enabled
+    }
+
+    pub fn rewind_enabled(&self) -> bool {
+        self.rewind_enabled
+    }
+
+    pub fn next_enabled(&self) -> bool {
+        self.next_enabled
+    }
+
+    pub fn previous_enabled(&self) -> bool {
+        self.previous_enabled
+    }
+
+    pub fn channel_up_enabled(&self) -> bool {
+        self.channel_up_enabled
+    }
+
+    pub fn channel_down_enabled(&self) -> bool {
+        self.channel_down_enabled
+    }
+
+    pub fn play_pause_toggle_enabled(&self) -> bool {
+        self.play_pause_toggle_enabled
+    }
+
+    pub fn shuffle_enabled(&self) -> bool {
+        self.shuffle_enabled
+    }
+
+    pub fn repeat_enabled(&self) -> bool {
+        self.repeat_enabled
+    }
+
+    pub fn playback_rate_enabled(&self) -> bool {
+        self.playback_rate_enabled
+    }
+
+    pub fn playback_position_enabled(&self) -> bool {
+        self.playback_position_enabled
+    }
+
+    pub fn playback_rate(&self) -> f64 {
+        self.playback_rate
+    }
+
+    pub fn volume(&self) -> f64 {
+        self.volume
+    }
+
     fn new(
         is_playing: bool,
         is_pausing: bool,
@@ -122,9 +435,19 @@ impl PlaybackState {
             repeat_enabled,
             playback_rate_enabled,
             playback_position_enabled,
+            playback_rate: 1.0,
+            volume: -1.0,
         }
     }
 
+    /// Rate and volume are kept out of `new` and of the `getPlaybackState` string, whose field
+    /// count Java callers rely on; `getPlaybackRate` and `getVolume` read them instead.
+    fn with_rate_and_volume(mut self, playback_rate: f64, volume: f64) -> Self {
+        self.playback_rate = playback_rate;
+        self.volume = volume;
+        self
+    }
+
     fn empty() -> Self {
         Self {
             is_playing: false,
@@ -150,6 +473,8 @@ impl PlaybackState {
             repeat_enabled: false,
             playback_rate_enabled: false,
             playback_position_enabled: false,
+            playback_rate: 1.0,
+            volume: -1.0,
         }
     }
 
@@ -159,333 +484,511 @@ impl PlaybackState {
             self.is_playing, self.is_pausing, self.is_stopped, self.is_shuffling, self.is_repeating_track, self.is_repeating_playlist, self.current_time, self.max_time, self.play_enabled, self.pause_enabled, self.stop_enabled, self.record_enabled, self.fast_forward_enabled, self.rewind_enabled, self.next_enabled, self.previous_enabled, self.channel_up_enabled, self.channel_down_enabled, self.play_pause_toggle_enabled, self.shuffle_enabled, self.repeat_enabled, self.playback_rate_enabled, self.playback_position_enabled
         )
     }
-}
 
-#[no_mangle]
-pub extern "system" fn Java_dev_yuzuki_libs_media_NativeMedia_getMediaInfo(_env: JNIEnv, _class: JClass) -> jstring {
+    pub fn status(&self) -> &'static str {
+        if self.is_playing {
+            "Playing"
+        } else if self.is_pausing {
+            "Paused"
+        } else if self.is_stopped {
+            "Stopped"
+        } else {
+            "Unavailable"
+        }
+    }
 
-    #[cfg(target_os = "windows")]
-    {
-        _env.new_string(platform::windows::get_media_info().to_string()).unwrap().into_raw()
+    pub fn repeat(&self) -> &'static str {
+        if self.is_repeating_track {
+            "Track"
+        } else if self.is_repeating_playlist {
+            "Playlist"
+        } else {
+            "None"
+        }
     }
 
-    #[cfg(target_os = "linux")]
-    {
-        _env.new_string(platform::linux::get_media_info().unwrap().to_string()).unwrap().into_raw()
+    /// Names of the enabled controls, spelled like the `NativeController` methods.
+    pub fn capabilities(&self) -> Vec<&'static str> {
+        [
+            ("play", self.play_enabled),
+            ("pause", self.pause_enabled),
+            ("stop", self.stop_enabled),
+            ("record", self.record_enabled),
+            ("fastForward", self.fast_forward_enabled),
+            ("rewind", self.rewind_enabled),
+            ("next", self.next_enabled),
+            ("previous", self.previous_enabled),
+            ("channelUp", self.channel_up_enabled),
+            ("channelDown", self.channel_down_enabled),
+            ("togglePlayPause", self.play_pause_toggle_enabled),
+            ("shuffle", self.shuffle_enabled),
+            ("repeat", self.repeat_enabled),
+            ("playbackRate", self.playback_rate_enabled),
+            ("playbackPosition", self.playback_position_enabled),
+        ]
+        .into_iter()
+        .filter_map(|(name, enabled)| enabled.then_some(name))
+        .collect()
     }
+}
 
-    #[cfg(target_os = "macos")]
-    {
-        _env.new_string(platform::macos::get_media_info().to_string()).unwrap().into_raw()
+/// Binds the natives through `RegisterNatives` when the library is loaded; see [`natives::register`].
+#[no_mangle]
+pub extern "system" fn JNI_OnLoad(vm: JavaVM, _reserved: *mut c_void) -> jint {
+    if let Ok(mut env) = vm.get_env() {
+        natives::register(&mut env);
     }
+    listener::init(vm);
+    JNI_VERSION_1_8
 }
 
+/// Runs when the class loader that loaded the library is collected, e.g. on a plugin reload.
 #[no_mangle]
-pub extern "system" fn Java_dev_yuzuki_libs_media_NativeMedia_getPlaybackState(_env: JNIEnv, _class: JClass) -> jstring {
+pub extern "system" fn JNI_OnUnload(_vm: JavaVM, _reserved: *mut c_void) {
+    shutdown();
+}
 
-    #[cfg(target_os = "windows")]
-    {
-        _env.new_string(platform::windows::get_playback_state().to_string()).unwrap().into_raw()
+/// `config` is comma separated `key=value` pairs: `pollingInterval` in milliseconds, and `events=true`
+/// to start the signal watcher and poller before the first listener. Unknown keys are ignored.
+#[no_mangle]
+pub extern "system" fn Java_dev_yuzuki_libs_media_NativeMedia_init(mut _env: JNIEnv, _class: JClass, config: JString) -> jboolean {
+    let config = get_string(&mut _env, &config).unwrap_or_default();
+    for entry in config.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+        let Some((key, value)) = entry.split_once('=') else {
+            return jboolean::from(false);
+        };
+        match key.trim() {
+            "pollingInterval" => match value.trim().parse::<u64>() {
+                Ok(millis) => poller::set_interval(Duration::from_millis(millis)),
+                Err(_) => return jboolean::from(false),
+            },
+            "events" if value.trim() == "true" => event::start(),
+            _ => {}
+        }
     }
+    JNI_TRUE
+}
 
-    #[cfg(target_os = "linux")]
-    {
-        _env.new_string(platform::linux::get_playback_state().unwrap().to_string()).unwrap().into_raw()
-    }
+#[no_mangle]
+pub extern "system" fn Java_dev_yuzuki_libs_media_NativeMedia_getNativeVersion(_env: JNIEnv, _class: JClass) -> jstring {
+    _env.new_string(media::VERSION).unwrap().into_raw()
+}
 
-    #[cfg(target_os = "macos")]
-    {
-        _env.new_string(platform::macos::get_playback_state().to_string()).unwrap().into_raw()
+/// Returns "backend=<name>" followed by the compiled features, comma separated.
+#[no_mangle]
+pub extern "system" fn Java_dev_yuzuki_libs_media_NativeMedia_getNativeFeatures(_env: JNIEnv, _class: JClass) -> jstring {
+    _env.new_string(format!("backend={},{}", media::backend(), media::features().join(","))).unwrap().into_raw()
+}
+
+/// Fills `template` in from the active player, see the `template` module for the syntax. Returns
+/// null when the template is invalid.
+#[no_mangle]
+pub extern "system" fn Java_dev_yuzuki_libs_media_NativeMedia_formatNowPlaying(mut _env: JNIEnv, _class: JClass, template: JString) -> jstring {
+    match get_string(&mut _env, &template).map(|t| template::Template::parse(&t)) {
+        Some(Ok(template)) => _env.new_string(template.render(&media::snapshot(), None)).unwrap().into_raw(),
+        _ => std::ptr::null_mut(),
     }
 }
 
+/// Keeps `directory` up to date with the now-playing text from `template`, the album art and a JSON
+/// snapshot, coalescing changes within `window_ms`. Returns the id for `stopOverlay`, or -1 when
+/// the template is invalid.
+#[cfg(feature = "overlay")]
 #[no_mangle]
-pub extern "system" fn Java_dev_yuzuki_libs_media_NativeController_play(_env: JNIEnv, _class: JClass) -> jboolean {
+pub extern "system" fn Java_dev_yuzuki_libs_media_NativeMedia_startOverlay(mut _env: JNIEnv, _class: JClass, directory: JString, template: JString, window_ms: jlong) -> jlong {
+    let (Some(directory), Some(template)) = (get_string(&mut _env, &directory), get_string(&mut _env, &template)) else {
+        return -1;
+    };
+    template::Template::parse(&template)
+        .map_or(-1, |template| overlay::Overlay::new(directory, template).start(Duration::from_millis(window_ms.max(0) as u64)))
+}
 
-    #[cfg(target_os = "windows")]
-    {
-        jboolean::from(platform::windows::try_play())
-    }
+#[cfg(feature = "overlay")]
+#[no_mangle]
+pub extern "system" fn Java_dev_yuzuki_libs_media_NativeMedia_stopOverlay(_env: JNIEnv, _class: JClass, id: jlong) -> jboolean {
+    jboolean::from(overlay::stop(id))
+}
 
-    #[cfg(target_os = "linux")]
-    {
-        jboolean::from(platform::linux::try_play())
+/// Serves snapshots, art, commands and a WebSocket of events on `address`, e.g. "127.0.0.1:8974".
+/// An empty or null `token` lets every request in. Returns the id for `stopServer`, or -1 when the
+/// address can't be bound.
+#[cfg(feature = "server")]
+#[no_mangle]
+pub extern "system" fn Java_dev_yuzuki_libs_media_NativeMedia_startServer(mut _env: JNIEnv, _class: JClass, address: JString, token: JString) -> jlong {
+    let Some(address) = get_string(&mut _env, &address) else {
+        return -1;
+    };
+    let mut server = server::Server::new(&address);
+    if let Some(token) = get_string(&mut _env, &token).filter(|t| !t.is_empty()) {
+        server = server.token(&token);
     }
+    server.start().unwrap_or(-1)
+}
 
-    #[cfg(target_os = "macos")]
-    {
-        jboolean::from(platform::macos::try_play())
-    }
+#[cfg(feature = "server")]
+#[no_mangle]
+pub extern "system" fn Java_dev_yuzuki_libs_media_NativeMedia_stopServer(_env: JNIEnv, _class: JClass, id: jlong) -> jboolean {
+    jboolean::from(server::stop(id))
 }
 
+/// Serves JSON-RPC on the Unix socket at `path`, or at `$XDG_RUNTIME_DIR/mediainfo.sock` for an
+/// empty or null one. Returns the id for `stopRpcServer`, or -1 when the socket can't be bound.
+#[cfg(all(unix, feature = "rpc"))]
 #[no_mangle]
-pub extern "system" fn Java_dev_yuzuki_libs_media_NativeController_pause(_env: JNIEnv, _class: JClass) -> jboolean {
-    #[cfg(target_os = "windows")]
-    {
-        jboolean::from(platform::windows::try_pause())
-    }
+pub extern "system" fn Java_dev_yuzuki_libs_media_NativeMedia_startRpcServer(mut _env: JNIEnv, _class: JClass, path: JString) -> jlong {
+    let server = match get_string(&mut _env, &path).filter(|p| !p.is_empty()) {
+        Some(path) => Some(rpc::Server::new(path)),
+        None => rpc::Server::with_default_path(),
+    };
+    server.and_then(|server| server.start().ok()).unwrap_or(-1)
+}
 
-    #[cfg(target_os = "linux")]
-    {
-        jboolean::from(platform::linux::try_pause())
-    }
+#[cfg(all(unix, feature = "rpc"))]
+#[no_mangle]
+pub extern "system" fn Java_dev_yuzuki_libs_media_NativeMedia_stopRpcServer(_env: JNIEnv, _class: JClass, id: jlong) -> jboolean {
+    jboolean::from(rpc::stop(id))
+}
 
-    #[cfg(target_os = "macos")]
-    {
-        jboolean::from(platform::macos::try_pause())
-    }
+/// Stops every background thread and releases the listeners. The library can be used again afterwards.
+#[no_mangle]
+pub extern "system" fn Java_dev_yuzuki_libs_media_NativeMedia_shutdown(_env: JNIEnv, _class: JClass) {
+    shutdown();
+}
+
+fn shutdown() {
+    event::stop();
+    poller::shutdown();
+    listener::shutdown();
+    #[cfg(feature = "overlay")]
+    overlay::shutdown();
+    #[cfg(feature = "server")]
+    server::shutdown();
+    #[cfg(all(unix, feature = "rpc"))]
+    rpc::shutdown();
+    trigger::shutdown();
+    generation::shutdown();
+    event::join();
 }
 
 #[no_mangle]
-pub extern "system" fn Java_dev_yuzuki_libs_media_NativeController_stop(_env: JNIEnv, _class: JClass) -> jboolean {
-    #[cfg(target_os = "windows")]
-    {
-        jboolean::from(platform::windows::try_stop())
-    }
+pub extern "system" fn Java_dev_yuzuki_libs_media_NativeMedia_getMediaInfo(_env: JNIEnv, _class: JClass) -> jstring {
+    _env.new_string(media::media_info().unwrap().to_string()).unwrap().into_raw()
+}
 
-    #[cfg(target_os = "linux")]
-    {
-        jboolean::from(platform::linux::try_stop())
-    }
+#[no_mangle]
+pub extern "system" fn Java_dev_yuzuki_libs_media_NativeMedia_getPlaybackState(_env: JNIEnv, _class: JClass) -> jstring {
+    _env.new_string(media::playback_state().unwrap().to_string()).unwrap().into_raw()
+}
 
-    #[cfg(target_os = "macos")]
-    {
-        jboolean::from(platform::macos::try_stop())
-    }
+#[no_mangle]
+pub extern "system" fn Java_dev_yuzuki_libs_media_NativeMedia_getPlaybackRate(_env: JNIEnv, _class: JClass) -> jdouble {
+    media::playback_state().map_or(1.0, |state| state.playback_rate())
 }
 
+/// Linear from 0.0 to 1.0, or -1.0 when the player doesn't report it.
 #[no_mangle]
-pub extern "system" fn Java_dev_yuzuki_libs_media_NativeController_record(_env: JNIEnv, _class: JClass) -> jboolean {
-    #[cfg(target_os = "windows")]
-    {
-        jboolean::from(platform::windows::try_record())
-    }
+pub extern "system" fn Java_dev_yuzuki_libs_media_NativeMedia_getVolume(_env: JNIEnv, _class: JClass) -> jdouble {
+    media::playback_state().map_or(-1.0, |state| state.volume())
+}
 
-    #[cfg(target_os = "linux")]
-    {
-        jboolean::from(platform::linux::try_record())
-    }
+#[no_mangle]
+pub extern "system" fn Java_dev_yuzuki_libs_media_NativeController_play(_env: JNIEnv, _class: JClass) -> jint {
+    command_result(media::play())
+}
 
-    #[cfg(target_os = "macos")]
-    {
-        jboolean::from(platform::macos::try_record())
-    }
+#[no_mangle]
+pub extern "system" fn Java_dev_yuzuki_libs_media_NativeController_pause(_env: JNIEnv, _class: JClass) -> jint {
+    command_result(media::pause())
 }
 
 #[no_mangle]
-pub extern "system" fn Java_dev_yuzuki_libs_media_NativeController_fastForward(_env: JNIEnv, _class: JClass) -> jboolean {
-    #[cfg(target_os = "windows")]
-    {
-        jboolean::from(platform::windows::try_fast_forward())
-    }
+pub extern "system" fn Java_dev_yuzuki_libs_media_NativeController_stop(_env: JNIEnv, _class: JClass) -> jint {
+    command_result(media::stop())
+}
 
-    #[cfg(target_os = "linux")]
-    {
-        jboolean::from(platform::linux::try_fast_forward())
-    }
+#[no_mangle]
+pub extern "system" fn Java_dev_yuzuki_libs_media_NativeController_record(_env: JNIEnv, _class: JClass) -> jint {
+    command_result(media::record())
+}
 
-    #[cfg(target_os = "macos")]
-    {
-        jboolean::from(platform::macos::try_fast_forward())
-    }
+#[no_mangle]
+pub extern "system" fn Java_dev_yuzuki_libs_media_NativeController_fastForward(_env: JNIEnv, _class: JClass) -> jint {
+    command_result(media::fast_forward())
 }
 
 #[no_mangle]
-pub extern "system" fn Java_dev_yuzuki_libs_media_NativeController_rewind(_env: JNIEnv, _class: JClass) -> jboolean {
-    #[cfg(target_os = "windows")]
-    {
-        jboolean::from(platform::windows::try_rewind())
-    }
+pub extern "system" fn Java_dev_yuzuki_libs_media_NativeController_rewind(_env: JNIEnv, _class: JClass) -> jint {
+    command_result(media::rewind())
+}
 
-    #[cfg(target_os = "linux")]
-    {
-        jboolean::from(platform::linux::try_rewind())
-    }
+#[no_mangle]
+pub extern "system" fn Java_dev_yuzuki_libs_media_NativeController_next(_env: JNIEnv, _class: JClass) -> jint {
+    command_result(media::next())
+}
 
-    #[cfg(target_os = "macos")]
-    {
-        jboolean::from(platform::macos::try_rewind())
-    }
+#[no_mangle]
+pub extern "system" fn Java_dev_yuzuki_libs_media_NativeController_previous(_env: JNIEnv, _class: JClass) -> jint {
+    command_result(media::previous())
 }
 
 #[no_mangle]
-pub extern "system" fn Java_dev_yuzuki_libs_media_NativeController_next(_env: JNIEnv, _class: JClass) -> jboolean {
-    #[cfg(target_os = "windows")]
-    {
-        jboolean::from(platform::windows::try_next())
-    }
+pub extern "system" fn Java_dev_yuzuki_libs_media_NativeController_channelUp(_env: JNIEnv, _class: JClass) -> jint {
+    command_result(media::channel_up())
+}
 
-    #[cfg(target_os = "linux")]
-    {
-        jboolean::from(platform::linux::try_next())
-    }
+#[no_mangle]
+pub extern "system" fn Java_dev_yuzuki_libs_media_NativeController_channelDown(_env: JNIEnv, _class: JClass) -> jint {
+    command_result(media::channel_down())
+}
 
-    #[cfg(target_os = "macos")]
-    {
-        jboolean::from(platform::macos::try_next())
-    }
+#[no_mangle]
+pub extern "system" fn Java_dev_yuzuki_libs_media_NativeController_togglePlayPause(_env: JNIEnv, _class: JClass) -> jint {
+    command_result(media::toggle_play_pause())
 }
 
 #[no_mangle]
-pub extern "system" fn Java_dev_yuzuki_libs_media_NativeController_previous(_env: JNIEnv, _class: JClass) -> jboolean {
-    #[cfg(target_os = "windows")]
-    {
-        jboolean::from(platform::windows::try_previous())
-    }
+pub extern "system" fn Java_dev_yuzuki_libs_media_NativeController_shuffle(_env: JNIEnv, _class: JClass, j_boolean: jboolean) -> jint {
+    command_result(media::shuffle(j_boolean == JNI_TRUE))
+}
 
-    #[cfg(target_os = "linux")]
-    {
-        jboolean::from(platform::linux::try_previous())
-    }
+#[no_mangle]
+pub extern "system" fn Java_dev_yuzuki_libs_media_NativeController_repeat(_env: JNIEnv, _class: JClass) -> jint {
+    command_result(media::repeat())
+}
 
-    #[cfg(target_os = "macos")]
-    {
-        jboolean::from(platform::macos::try_previous()  )
-    }
+#[no_mangle]
+pub extern "system" fn Java_dev_yuzuki_libs_media_NativeController_playbackRate(_env: JNIEnv, _class: JClass, j_double: jdouble) -> jint {
+    command_result(media::set_playback_rate(j_double))
 }
 
 #[no_mangle]
-pub extern "system" fn Java_dev_yuzuki_libs_media_NativeController_channelUp(_env: JNIEnv, _class: JClass) -> jboolean {
-    #[cfg(target_os = "windows")]
-    {
-        jboolean::from(platform::windows::try_change_channel_up())
-    }
+pub extern "system" fn Java_dev_yuzuki_libs_media_NativeController_playbackPosition(_env: JNIEnv, _class: JClass, j_long: jlong) -> jint {
+    command_result(media::set_playback_position(j_long))
+}
 
-    #[cfg(target_os = "linux")]
-    {
-        jboolean::from(platform::linux::try_change_channel_up())
-    }
+#[no_mangle]
+pub extern "system" fn Java_dev_yuzuki_libs_media_NativeController_volume(_env: JNIEnv, _class: JClass, j_double: jdouble) -> jint {
+    command_result(media::set_volume(j_double))
+}
 
-    #[cfg(target_os = "macos")]
-    {
-        jboolean::from(platform::macos::try_change_channel_up())
-    }
+#[no_mangle]
+pub extern "system" fn Java_dev_yuzuki_libs_media_NativeController_openUri(mut _env: JNIEnv, _class: JClass, j_string: JString) -> jint {
+    let Some(uri) = get_string(&mut _env, &j_string) else {
+        return command_result(CommandResult::Failed("invalid string argument".to_string()));
+    };
+
+    command_result(media::open_uri(&uri))
 }
 
 #[no_mangle]
-pub extern "system" fn Java_dev_yuzuki_libs_media_NativeController_channelDown(_env: JNIEnv, _class: JClass) -> jboolean {
-    #[cfg(target_os = "windows")]
-    {
-        jboolean::from(platform::windows::try_change_channel_down())
-    }
+pub extern "system" fn Java_dev_yuzuki_libs_media_NativeMedia_getSupportedUriSchemes(_env: JNIEnv, _class: JClass) -> jstring {
+    _env.new_string(media::supported_uri_schemes().join(",")).unwrap().into_raw()
+}
 
-    #[cfg(target_os = "linux")]
-    {
-        jboolean::from(platform::linux::try_change_channel_down())
-    }
+#[no_mangle]
+pub extern "system" fn Java_dev_yuzuki_libs_media_NativeMedia_getSupportedMimeTypes(_env: JNIEnv, _class: JClass) -> jstring {
+    _env.new_string(media::supported_mime_types().join(",")).unwrap().into_raw()
+}
 
-    #[cfg(target_os = "macos")]
-    {
-        jboolean::from(platform::macos::try_change_channel_down())
-    }
+#[no_mangle]
+pub extern "system" fn Java_dev_yuzuki_libs_media_NativeController_playerAvailable(_env: JNIEnv, _class: JClass) -> jboolean {
+    jboolean::from(media::player_available())
 }
 
 #[no_mangle]
-pub extern "system" fn Java_dev_yuzuki_libs_media_NativeController_togglePlayPause(_env: JNIEnv, _class: JClass) -> jboolean {
-    #[cfg(target_os = "windows")]
-    {
-        jboolean::from(platform::windows::try_play_pause_toggle())
-    }
+pub extern "system" fn Java_dev_yuzuki_libs_media_NativeController_raise(_env: JNIEnv, _class: JClass) -> jint {
+    command_result(media::raise())
+}
 
-    #[cfg(target_os = "linux")]
-    {
-        jboolean::from(platform::linux::try_play_pause_toggle())
-    }
+#[no_mangle]
+pub extern "system" fn Java_dev_yuzuki_libs_media_NativeController_quit(_env: JNIEnv, _class: JClass) -> jint {
+    command_result(media::quit())
+}
 
-    #[cfg(target_os = "macos")]
-    {
-        jboolean::from(platform::macos::try_play_pause_toggle())
-    }
+#[no_mangle]
+pub extern "system" fn Java_dev_yuzuki_libs_media_NativeController_fullscreen(_env: JNIEnv, _class: JClass, j_boolean: jboolean) -> jint {
+    command_result(media::set_fullscreen(j_boolean == JNI_TRUE))
 }
 
 #[no_mangle]
-pub extern "system" fn Java_dev_yuzuki_libs_media_NativeController_shuffle(_env: JNIEnv, _class: JClass, j_boolean: jboolean) -> jboolean {
-    #[cfg(target_os = "windows")]
-    {
-        jboolean::from(platform::windows::try_change_shuffle(j_boolean == JNI_TRUE))
-    }
+pub extern "system" fn Java_dev_yuzuki_libs_media_NativeController_canRaise(_env: JNIEnv, _class: JClass) -> jboolean {
+    jboolean::from(media::can_raise())
+}
 
-    #[cfg(target_os = "linux")]
-    {
-        jboolean::from(platform::linux::try_change_shuffle())
-    }
+#[no_mangle]
+pub extern "system" fn Java_dev_yuzuki_libs_media_NativeController_canQuit(_env: JNIEnv, _class: JClass) -> jboolean {
+    jboolean::from(media::can_quit())
+}
 
-    #[cfg(target_os = "macos")]
-    {
-        jboolean::from(platform::macos::try_change_shuffle())
-    }
+#[no_mangle]
+pub extern "system" fn Java_dev_yuzuki_libs_media_NativeController_canSetFullscreen(_env: JNIEnv, _class: JClass) -> jboolean {
+    jboolean::from(media::can_set_fullscreen())
 }
 
 #[no_mangle]
-pub extern "system" fn Java_dev_yuzuki_libs_media_NativeController_repeat(_env: JNIEnv, _class: JClass) -> jboolean {
-    #[cfg(target_os = "windows")]
-    {
-        jboolean::from(platform::windows::try_change_repeat())
-    }
+pub extern "system" fn Java_dev_yuzuki_libs_media_NativeController_hasTrackList(_env: JNIEnv, _class: JClass) -> jboolean {
+    jboolean::from(media::has_track_list())
+}
 
-    #[cfg(target_os = "linux")]
-    {
-        jboolean::from(platform::linux::try_change_repeat())
-    }
+#[no_mangle]
+pub extern "system" fn Java_dev_yuzuki_libs_media_NativeMedia_getTrackList(mut _env: JNIEnv, _class: JClass) -> jobjectArray {
+    new_string_array(&mut _env, media::track_list().into_iter().map(|t| t.to_string()).collect())
+}
 
-    #[cfg(target_os = "macos")]
-    {
-        jboolean::from(platform::macos::try_change_repeat())
-    }
+#[no_mangle]
+pub extern "system" fn Java_dev_yuzuki_libs_media_NativeController_goTo(mut _env: JNIEnv, _class: JClass, j_string: JString) -> jint {
+    let Some(track_id) = get_string(&mut _env, &j_string) else {
+        return command_result(CommandResult::Failed("invalid string argument".to_string()));
+    };
+
+    command_result(media::go_to(&track_id))
 }
 
 #[no_mangle]
-pub extern "system" fn Java_dev_yuzuki_libs_media_NativeController_playbackRate(_env: JNIEnv, _class: JClass, j_double: jdouble) -> jboolean {
-    #[cfg(target_os = "windows")]
-    {
-        jboolean::from(platform::windows::try_change_playback_rate(j_double))
-    }
+pub extern "system" fn Java_dev_yuzuki_libs_media_NativeController_addTrack(mut _env: JNIEnv, _class: JClass, j_uri: JString, j_after_track_id: JString, j_boolean: jboolean) -> jint {
+    let Some(uri) = get_string(&mut _env, &j_uri) else {
+        return command_result(CommandResult::Failed("invalid string argument".to_string()));
+    };
+    // A null or empty track id adds the track at the start of the list.
+    let after_track_id = get_string(&mut _env, &j_after_track_id).filter(|id| !id.is_empty());
+
+    command_result(media::add_track(&uri, after_track_id.as_deref(), j_boolean == JNI_TRUE))
+}
 
-    #[cfg(target_os = "linux")]
-    {
-        jboolean::from(platform::linux::try_change_playback_rate(j_double))
-    }
+#[no_mangle]
+pub extern "system" fn Java_dev_yuzuki_libs_media_NativeController_removeTrack(mut _env: JNIEnv, _class: JClass, j_string: JString) -> jint {
+    let Some(track_id) = get_string(&mut _env, &j_string) else {
+        return command_result(CommandResult::Failed("invalid string argument".to_string()));
+    };
 
-    #[cfg(target_os = "macos")]
-    {
-        jboolean::from(platform::macos::try_change_playback_rate(j_double))
-    }
+    command_result(media::remove_track(&track_id))
 }
 
 #[no_mangle]
-pub extern "system" fn Java_dev_yuzuki_libs_media_NativeController_playbackPosition(_env: JNIEnv, _class: JClass, j_long: jlong) -> jboolean {
-    #[cfg(target_os = "windows")]
-    {
-        jboolean::from(platform::windows::try_change_playback_position(j_long))
-    }
+pub extern "system" fn Java_dev_yuzuki_libs_media_NativeMedia_getPlaylists(mut _env: JNIEnv, _class: JClass, j_index: jint, j_max_count: jint, j_order: JString, j_boolean: jboolean) -> jobjectArray {
+    let order = get_string(&mut _env, &j_order).unwrap_or("Alphabetical".to_string());
+    let playlists = media::playlists(j_index.max(0) as u32, j_max_count.max(0) as u32, &order, j_boolean == JNI_TRUE);
 
-    #[cfg(target_os = "linux")]
-    {
-        jboolean::from(platform::linux::try_change_playback_position(j_long))
-    }
+    new_string_array(&mut _env, playlists.into_iter().map(|p| p.to_string()).collect())
+}
 
-    #[cfg(target_os = "macos")]
-    {
-        jboolean::from(platform::macos::try_change_playback_position(j_long))
-    }
+#[no_mangle]
+pub extern "system" fn Java_dev_yuzuki_libs_media_NativeMedia_getPlaylistOrderings(_env: JNIEnv, _class: JClass) -> jstring {
+    _env.new_string(media::playlist_orderings().join(",")).unwrap().into_raw()
 }
 
 #[no_mangle]
-pub extern "system" fn Java_dev_yuzuki_libs_media_NativeController_playerAvailable(_env: JNIEnv, _class: JClass) -> jboolean {
-    #[cfg(target_os = "windows")]
-    {
-        jboolean::from(!platform::windows::unavailable())
+pub extern "system" fn Java_dev_yuzuki_libs_media_NativeMedia_getPlaylistCount(_env: JNIEnv, _class: JClass) -> jint {
+    media::playlist_count() as jint
+}
+
+#[no_mangle]
+pub extern "system" fn Java_dev_yuzuki_libs_media_NativeMedia_getActivePlaylist(_env: JNIEnv, _class: JClass) -> jstring {
+    _env.new_string(media::active_playlist().unwrap_or(PlaylistInfo::empty()).to_string()).unwrap().into_raw()
+}
+
+#[no_mangle]
+pub extern "system" fn Java_dev_yuzuki_libs_media_NativeController_activatePlaylist(mut _env: JNIEnv, _class: JClass, j_string: JString) -> jint {
+    let Some(playlist_id) = get_string(&mut _env, &j_string) else {
+        return command_result(CommandResult::Failed("invalid string argument".to_string()));
+    };
+
+    command_result(media::activate_playlist(&playlist_id))
+}
+
+#[no_mangle]
+pub extern "system" fn Java_dev_yuzuki_libs_media_NativeMedia_addListener(mut _env: JNIEnv, _class: JClass, listener: JObject) -> jboolean {
+    jboolean::from(listener::add(&mut _env, &listener))
+}
+
+#[no_mangle]
+pub extern "system" fn Java_dev_yuzuki_libs_media_NativeMedia_removeListener(mut _env: JNIEnv, _class: JClass, listener: JObject) -> jboolean {
+    jboolean::from(listener::remove(&mut _env, &listener))
+}
+
+#[no_mangle]
+pub extern "system" fn Java_dev_yuzuki_libs_media_NativeController_getLastError(_env: JNIEnv, _class: JClass) -> jstring {
+    let message = LAST_ERROR.with(|e| e.borrow().clone());
+    _env.new_string(message).unwrap().into_raw()
+}
+
+fn command_result(result: CommandResult) -> jint {
+    let code = result.code();
+    if let CommandResult::Failed(message) = result {
+        LAST_ERROR.with(|e| *e.borrow_mut() = message);
     }
+    code
+}
+
+#[no_mangle]
+pub extern "system" fn Java_dev_yuzuki_libs_media_NativeMedia_setListenerWindow(mut _env: JNIEnv, _class: JClass, listener: JObject, j_long: jlong) -> jboolean {
+    jboolean::from(listener::set_window(&mut _env, &listener, Duration::from_millis(j_long.max(0) as u64)))
+}
+
+#[no_mangle]
+pub extern "system" fn Java_dev_yuzuki_libs_media_NativeMedia_setProgressInterval(mut _env: JNIEnv, _class: JClass, listener: JObject, j_long: jlong) -> jboolean {
+    jboolean::from(listener::set_progress_interval(&mut _env, &listener, Duration::from_millis(j_long.max(0) as u64)))
+}
+
+#[no_mangle]
+pub extern "system" fn Java_dev_yuzuki_libs_media_NativeMedia_setPollingInterval(_env: JNIEnv, _class: JClass, j_long: jlong) {
+    poller::set_interval(Duration::from_millis(j_long.max(0) as u64));
+}
+
+#[no_mangle]
+pub extern "system" fn Java_dev_yuzuki_libs_media_NativeMedia_getPolledPlayers(_env: JNIEnv, _class: JClass) -> jstring {
+    _env.new_string(poller::polled_players().join(",")).unwrap().into_raw()
+}
+
+#[no_mangle]
+pub extern "system" fn Java_dev_yuzuki_libs_media_NativeMedia_getGeneration(_env: JNIEnv, _class: JClass) -> jlong {
+    generation::current().0
+}
+
+/// Returns "generation\nmedia\nstate"; the generation is unchanged when the timeout expired first.
+#[no_mangle]
+pub extern "system" fn Java_dev_yuzuki_libs_media_NativeMedia_waitForChange(_env: JNIEnv, _class: JClass, generation: jlong, timeout_ms: jlong) -> jstring {
+    let (generation, snapshot) = generation::wait_for_change(generation, Duration::from_millis(timeout_ms.max(0) as u64));
+    _env.new_string(format!("{}\n{}", generation, snapshot)).unwrap().into_raw()
+}
+
+/// `kind` is "at" or "beforeEnd", `position` is in the units of `currentTime`. Returns the trigger id, or -1.
+#[no_mangle]
+pub extern "system" fn Java_dev_yuzuki_libs_media_NativeMedia_addPositionTrigger(mut _env: JNIEnv, _class: JClass, kind: JString, position: jlong) -> jlong {
+    get_string(&mut _env, &kind)
+        .and_then(|kind| trigger::Kind::parse(&kind, position))
+        .map_or(-1, trigger::add)
+}
 
-    #[cfg(target_os = "linux")]
-    {
-        jboolean::from(true)
+#[no_mangle]
+pub extern "system" fn Java_dev_yuzuki_libs_media_NativeMedia_removePositionTrigger(_env: JNIEnv, _class: JClass, id: jlong) -> jboolean {
+    jboolean::from(trigger::remove(id))
+}
+
+fn get_string(env: &mut JNIEnv, j_string: &JString) -> Option<String> {
+    if j_string.is_null() {
+        return None;
     }
+    env.get_string(j_string).ok().map(|s| s.into())
+}
+
+/// Returns null if the JVM couldn't allocate the array, leaving its exception pending for the caller.
+fn new_string_array(env: &mut JNIEnv, strings: Vec<String>) -> jobjectArray {
+    let array = (|| -> jni::errors::Result<_> {
+        let array = env.new_object_array(strings.len() as i32, "java/lang/String", JObject::null())?;
+        for (i, string) in strings.into_iter().enumerate() {
+            let element = env.new_string(string)?;
+            env.set_object_array_element(&array, i as i32, &element)?;
+            env.delete_local_ref(element)?;
+        }
+        Ok(array)
+    })();
+    array.map_or(std::ptr::null_mut(), |array| array.into_raw())
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{platform, MediaInfo, PlaybackState};
+    use crate::{platform, CommandResult, MediaInfo, PlaybackState};
     use std::thread::sleep;
     use std::time::Duration;
 
@@ -503,17 +1006,26 @@ mod tests {
             println!("{}", platform::windows::get_media_info().to_string());
             println!("{}", platform::windows::get_playback_state().to_string());
 
-            assert_eq!(platform::windows::try_pause(), true);
+            assert_eq!(platform::windows::try_pause(), CommandResult::Ok);
             sleep(Duration::from_secs(2));
-            assert_eq!(platform::windows::try_play(), true);
+            assert_eq!(platform::windows::try_play(), CommandResult::Ok);
             sleep(Duration::from_secs(2));
-            assert_eq!(platform::windows::try_next(), true);
+            assert_eq!(platform::windows::try_next(), CommandResult::Ok);
             sleep(Duration::from_secs(2));
-            assert_eq!(platform::windows::try_previous(), true);
+            assert_eq!(platform::windows::try_previous(), CommandResult::Ok);
             sleep(Duration::from_secs(2));
         }
      }
 
+    #[test]
+    fn command_result_codes() {
+        assert_eq!(CommandResult::Ok.code(), 0);
+        assert_eq!(CommandResult::NotSupported.code(), 1);
+        assert_eq!(CommandResult::NoPlayer.code(), 2);
+        assert_eq!(CommandResult::CapabilityDisabled.code(), 3);
+        assert_eq!(CommandResult::Failed("".to_string()).code(), 4);
+    }
+
     #[test]
     fn empty_info_text() {
         println!("{}", MediaInfo::empty().to_string());
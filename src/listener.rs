@@ -0,0 +1,261 @@
+use {
+    crate::{
+        event::{self, Event, Window},
+        media,
+        position,
+        Snapshot
+    },
+    jni::{
+        objects::{GlobalRef, JObject, JValue},
+        JNIEnv,
+        JavaVM
+    },
+    std::{
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            mpsc::{self, Receiver, RecvTimeoutError, Sender},
+            Mutex,
+            OnceLock
+        },
+        time::{Duration, Instant}
+    }
+};
+
+/// Java listeners are plain objects with a `void onEvent(String name, String data)` method.
+const ON_EVENT: &str = "onEvent";
+const ON_EVENT_SIGNATURE: &str = "(Ljava/lang/String;Ljava/lang/String;)V";
+
+/// How long the dispatcher sleeps when no listener has a pending snapshot.
+const IDLE_TIMEOUT: Duration = Duration::from_secs(60);
+
+struct Listener {
+    object: GlobalRef,
+    window: Window,
+    /// The snapshot this listener saw last, diffed against the next one for typed events.
+    last: Option<Snapshot>,
+    progress: Option<Progress>,
+}
+
+/// Progress ticks for one listener; `next` is `None` while playback isn't moving.
+struct Progress {
+    interval: Duration,
+    next: Option<Instant>,
+}
+
+static LISTENERS: Mutex<Vec<Listener>> = Mutex::new(Vec::new());
+static DISPATCHER: AtomicBool = AtomicBool::new(false);
+
+/// The dispatcher's own channel. Events arrive as `Some`; `None` only wakes it to recompute its
+/// deadlines, without touching anything on the event bus.
+static WAKE: Mutex<Option<Sender<Option<Event>>>> = Mutex::new(None);
+
+/// Captured in `JNI_OnLoad`, or from the first `addListener` call.
+static VM: OnceLock<JavaVM> = OnceLock::new();
+
+pub(crate) fn init(vm: JavaVM) {
+    let _ = VM.set(vm);
+}
+
+pub(crate) fn add(env: &mut JNIEnv, listener: &JObject) -> bool {
+    if listener.is_null() {
+        return false;
+    }
+
+    let Ok(object) = env.new_global_ref(listener) else {
+        return false;
+    };
+
+    let vm = match VM.get() {
+        Some(vm) => vm,
+        None => match env.get_java_vm() {
+            Ok(vm) => VM.get_or_init(|| vm),
+            Err(_) => return false,
+        },
+    };
+    if !DISPATCHER.swap(true, Ordering::SeqCst) {
+        start(vm);
+    }
+
+    LISTENERS.lock().unwrap().push(Listener {
+        object,
+        window: Window::new(Duration::ZERO),
+        last: None,
+        progress: None,
+    });
+    true
+}
+
+pub(crate) fn remove(env: &mut JNIEnv, listener: &JObject) -> bool {
+    let mut listeners = LISTENERS.lock().unwrap();
+    let before = listeners.len();
+    listeners.retain(|l| !env.is_same_object(&l.object, listener).unwrap_or(false));
+    listeners.len() != before
+}
+
+/// Sets how long `listener` waits after a change before receiving the coalesced snapshot.
+pub(crate) fn set_window(env: &mut JNIEnv, listener: &JObject, window: Duration) -> bool {
+    let mut listeners = LISTENERS.lock().unwrap();
+    match listeners.iter_mut().find(|l| env.is_same_object(&l.object, listener).unwrap_or(false)) {
+        Some(l) => {
+            l.window.window = window;
+            true
+        }
+        None => false,
+    }
+}
+
+/// Sends `listener` a `Progress` event every `interval` while playing; a zero interval stops them.
+pub(crate) fn set_progress_interval(env: &mut JNIEnv, listener: &JObject, interval: Duration) -> bool {
+    let mut listeners = LISTENERS.lock().unwrap();
+    match listeners.iter_mut().find(|l| env.is_same_object(&l.object, listener).unwrap_or(false)) {
+        Some(l) => {
+            l.progress = (!interval.is_zero()).then_some(Progress {
+                interval,
+                next: None,
+            });
+            drop(listeners);
+            // Ticks read the position cache, which nothing may have filled yet.
+            if !interval.is_zero() {
+                Snapshot::take();
+            }
+            // Wake the dispatcher so the first tick isn't held up by an idle wait.
+            if let Some(wake) = WAKE.lock().unwrap().as_ref() {
+                let _ = wake.send(None);
+            }
+            true
+        }
+        None => false,
+    }
+}
+
+/// Releases every listener's global ref; the dispatcher ends with its subscription in `event::stop`.
+pub(crate) fn shutdown() {
+    DISPATCHER.store(false, Ordering::SeqCst);
+    LISTENERS.lock().unwrap().clear();
+    WAKE.lock().unwrap().take();
+}
+
+fn start(vm: &'static JavaVM) {
+    let (sender, receiver) = mpsc::channel();
+    *WAKE.lock().unwrap() = Some(sender.clone());
+    event::subscribe(move |event| sender.send(Some(event.clone())).is_ok());
+
+    event::spawn(move || dispatch(vm, receiver));
+}
+
+fn dispatch(vm: &'static JavaVM, receiver: Receiver<Option<Event>>) {
+    let Ok(mut env) = vm.attach_current_thread_as_daemon() else {
+        return;
+    };
+
+    loop {
+        let timeout = LISTENERS.lock().unwrap()
+            .iter()
+            .flat_map(|l| [l.window.deadline(), l.progress.as_ref().and_then(|p| p.next)])
+            .flatten()
+            .min()
+            .map_or(IDLE_TIMEOUT, |deadline| deadline.saturating_duration_since(Instant::now()));
+
+        match receiver.recv_timeout(timeout) {
+            Ok(Some(Event::PropertiesChanged)) => {
+                let now = Instant::now();
+                LISTENERS.lock().unwrap().iter_mut().for_each(|l| l.window.touch(now));
+            }
+            Ok(Some(event)) => {
+                let listeners = LISTENERS.lock().unwrap().iter().map(|l| l.object.clone()).collect::<Vec<_>>();
+                notify(&mut env, &listeners, event);
+            }
+            Ok(None) | Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => return,
+        }
+
+        let now = Instant::now();
+        if LISTENERS.lock().unwrap().iter().any(|l| l.window.deadline().is_some_and(|d| d <= now)) {
+            flush(&mut env, now);
+        }
+        tick(&mut env, Instant::now());
+    }
+}
+
+/// Sends due progress ticks, and starts or stops them as playback resumes or pauses.
+fn tick(env: &mut JNIEnv, now: Instant) {
+    let ticking = LISTENERS.lock().unwrap().iter().any(|l| l.progress.is_some());
+    // The cache outlives a player that vanished without a word, so ask for one before ticking.
+    let playing = ticking && position::playing() && media::player_available();
+    let position = position::current();
+
+    let due = LISTENERS.lock().unwrap()
+        .iter_mut()
+        .filter_map(|l| {
+            let progress = l.progress.as_mut()?;
+            if !playing {
+                progress.next = None;
+                return None;
+            }
+
+            // Resuming ticks right away, then every interval from there.
+            let next = *progress.next.get_or_insert(now);
+            if next > now {
+                return None;
+            }
+            let following = next.checked_add(progress.interval)
+                .filter(|following| *following > now)
+                .or_else(|| now.checked_add(progress.interval));
+            match following {
+                Some(following) => progress.next = Some(following),
+                // An interval too long to schedule another tick makes this the last one.
+                None => l.progress = None,
+            }
+            Some(l.object.clone())
+        })
+        .collect::<Vec<_>>();
+
+    if let Some(position) = position {
+        for listener in due {
+            notify(env, std::slice::from_ref(&listener), Event::Progress(position));
+        }
+    }
+}
+
+/// Sends listeners whose window is over the typed events since their last snapshot, then the snapshot itself.
+fn flush(env: &mut JNIEnv, now: Instant) {
+    let snapshot = Snapshot::take();
+
+    let due = LISTENERS.lock().unwrap()
+        .iter_mut()
+        .filter_map(|l| l.window.take_if_due(now).then_some(l))
+        .map(|l| {
+            let mut events = l.last.as_ref().map(|last| Event::diff(last, &snapshot)).unwrap_or_default();
+            events.push(Event::Changed(snapshot.clone()));
+            l.last = Some(snapshot.clone());
+            (l.object.clone(), events)
+        })
+        .collect::<Vec<_>>();
+
+    for (listener, events) in due {
+        for event in events {
+            notify(env, std::slice::from_ref(&listener), event);
+        }
+    }
+}
+
+fn notify(env: &mut JNIEnv, listeners: &[GlobalRef], event: Event) {
+    if listeners.is_empty() {
+        return;
+    }
+
+    let name = event.name();
+    let data = event.to_string();
+    let _ = env.with_local_frame(8, |env| -> jni::errors::Result<()> {
+        let name = env.new_string(name)?;
+        let data = env.new_string(data)?;
+
+        for listener in listeners {
+            if env.call_method(listener, ON_EVENT, ON_EVENT_SIGNATURE, &[JValue::Object(&name), JValue::Object(&data)]).is_err() {
+                // A throwing listener must not take the others down with it.
+                env.exception_clear()?;
+            }
+        }
+        Ok(())
+    });
+}
@@ -0,0 +1,847 @@
+//! The backend behind the JNI exports, for use from Rust. Each function picks the platform
+//! implementation for the target it is compiled for.
+
+use {
+    crate::{
+        event::{self, Event},
+        platform,
+        CommandResult,
+        MediaInfo,
+        PlaybackState,
+        PlaylistInfo,
+        Snapshot,
+        TrackInfo
+    },
+    anyhow::Result,
+    std::{
+        path::{Path, PathBuf},
+        time::Duration
+    }
+};
+
+/// The crate version this library was built from.
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Which platform API the backend talks to.
+pub fn backend() -> &'static str {
+    #[cfg(target_os = "windows")]
+    {
+        "windows-gsmtc"
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        "linux-mpris"
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        "macos-mediaplayer"
+    }
+}
+
+/// The optional parts compiled into this build, so callers built against another version can check
+/// before reaching for them.
+pub fn features() -> Vec<&'static str> {
+    let mut features = vec!["events", "progress", "positionTriggers", "generations", "polling", "lifecycle", "template"];
+    if cfg!(feature = "stream") {
+        features.push("stream");
+    }
+    if cfg!(feature = "serde") {
+        features.push("serde");
+    }
+    if cfg!(feature = "capi") {
+        features.push("capi");
+    }
+    if cfg!(feature = "python") {
+        features.push("python");
+    }
+    if cfg!(feature = "overlay") {
+        features.push("overlay");
+    }
+    if cfg!(feature = "server") {
+        features.push("server");
+    }
+    if cfg!(all(unix, feature = "rpc")) {
+        features.push("rpc");
+    }
+    features
+}
+
+/// How many units of `currentTime` and `maxTime` make a second on this platform.
+pub fn time_units_per_second() -> i64 {
+    #[cfg(target_os = "windows")]
+    {
+        10_000_000
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        1_000_000
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        1
+    }
+}
+
+pub fn media_info() -> Result<MediaInfo> {
+    #[cfg(target_os = "windows")]
+    {
+        Ok(platform::windows::get_media_info())
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        platform::linux::get_media_info()
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        Ok(platform::macos::get_media_info())
+    }
+}
+
+pub fn playback_state() -> Result<PlaybackState> {
+    #[cfg(target_os = "windows")]
+    {
+        Ok(platform::windows::get_playback_state())
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        platform::linux::get_playback_state()
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        Ok(platform::macos::get_playback_state())
+    }
+}
+
+/// Media info and playback state read together, with "Unavailable" placeholders when there is no player.
+pub fn snapshot() -> Snapshot {
+    Snapshot::take()
+}
+
+/// The file behind `album_art`, a path or `file://` URL depending on the platform, if it is a
+/// local file that exists.
+pub fn album_art_file(media_info: &MediaInfo) -> Option<PathBuf> {
+    let art = media_info.album_art();
+    let path = match art.strip_prefix("file://") {
+        Some(path) => PathBuf::from(percent_decode(path)?),
+        None if Path::new(art).is_absolute() => PathBuf::from(art),
+        None => return None,
+    };
+    path.is_file().then_some(path)
+}
+
+/// Bus or app name and display identity of every player the platform knows about.
+pub fn players() -> Vec<(String, String)> {
+    #[cfg(target_os = "windows")]
+    {
+        platform::windows::get_players()
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        platform::linux::get_players().unwrap_or_default()
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        platform::macos::get_players()
+    }
+}
+
+/// Sends every following call to the player called `name` (see [`players`]) instead of the active
+/// one, or back to the active one for `None`.
+pub fn select_player(name: Option<&str>) {
+    #[cfg(target_os = "windows")]
+    {
+        platform::windows::select_player(name)
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        platform::linux::select_player(name)
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        platform::macos::select_player(name)
+    }
+}
+
+/// Runs `f` with the calls it makes on this thread going to the player called `name`, leaving the
+/// selection that other threads and the event sources follow alone.
+pub fn with_player<T>(name: &str, f: impl FnOnce() -> T) -> T {
+    #[cfg(target_os = "windows")]
+    {
+        platform::windows::with_player(name, f)
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        platform::linux::with_player(name, f)
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        platform::macos::with_player(name, f)
+    }
+}
+
+/// Calls `send` with every event the Java listeners would get, coalescing a burst of property
+/// changes into one snapshot sent `window` after its first change. The first event is the current
+/// snapshot. Stops once `send` returns `false`.
+pub fn subscribe(window: Duration, send: impl FnMut(Event) -> bool + Send + 'static) {
+    event::subscribe_coalesced(window, send)
+}
+
+pub fn player_available() -> bool {
+    #[cfg(target_os = "windows")]
+    {
+        !platform::windows::unavailable()
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        platform::linux::active_player_name().is_some()
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        true
+    }
+}
+
+pub fn play() -> CommandResult {
+    #[cfg(target_os = "windows")]
+    {
+        platform::windows::try_play()
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        platform::linux::try_play()
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        platform::macos::try_play()
+    }
+}
+
+pub fn pause() -> CommandResult {
+    #[cfg(target_os = "windows")]
+    {
+        platform::windows::try_pause()
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        platform::linux::try_pause()
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        platform::macos::try_pause()
+    }
+}
+
+pub fn stop() -> CommandResult {
+    #[cfg(target_os = "windows")]
+    {
+        platform::windows::try_stop()
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        platform::linux::try_stop()
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        platform::macos::try_stop()
+    }
+}
+
+pub fn record() -> CommandResult {
+    #[cfg(target_os = "windows")]
+    {
+        platform::windows::try_record()
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        platform::linux::try_record()
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        platform::macos::try_record()
+    }
+}
+
+pub fn fast_forward() -> CommandResult {
+    #[cfg(target_os = "windows")]
+    {
+        platform::windows::try_fast_forward()
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        platform::linux::try_fast_forward()
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        platform::macos::try_fast_forward()
+    }
+}
+
+pub fn rewind() -> CommandResult {
+    #[cfg(target_os = "windows")]
+    {
+        platform::windows::try_rewind()
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        platform::linux::try_rewind()
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        platform::macos::try_rewind()
+    }
+}
+
+pub fn next() -> CommandResult {
+    #[cfg(target_os = "windows")]
+    {
+        platform::windows::try_next()
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        platform::linux::try_next()
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        platform::macos::try_next()
+    }
+}
+
+pub fn previous() -> CommandResult {
+    #[cfg(target_os = "windows")]
+    {
+        platform::windows::try_previous()
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        platform::linux::try_previous()
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        platform::macos::try_previous()
+    }
+}
+
+pub fn channel_up() -> CommandResult {
+    #[cfg(target_os = "windows")]
+    {
+        platform::windows::try_change_channel_up()
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        platform::linux::try_change_channel_up()
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        platform::macos::try_change_channel_up()
+    }
+}
+
+pub fn channel_down() -> CommandResult {
+    #[cfg(target_os = "windows")]
+    {
+        platform::windows::try_change_channel_down()
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        platform::linux::try_change_channel_down()
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        platform::macos::try_change_channel_down()
+    }
+}
+
+pub fn toggle_play_pause() -> CommandResult {
+    #[cfg(target_os = "windows")]
+    {
+        platform::windows::try_play_pause_toggle()
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        platform::linux::try_play_pause_toggle()
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        platform::macos::try_play_pause_toggle()
+    }
+}
+
+/// Turns shuffle on or off. On macOS turning it on shuffles by song, unless it already shuffles by album.
+pub fn shuffle(shuffle: bool) -> CommandResult {
+    #[cfg(target_os = "windows")]
+    {
+        platform::windows::try_change_shuffle(shuffle)
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        platform::linux::try_change_shuffle(shuffle)
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        platform::macos::try_change_shuffle(shuffle)
+    }
+}
+
+pub fn repeat() -> CommandResult {
+    #[cfg(target_os = "windows")]
+    {
+        platform::windows::try_change_repeat()
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        platform::linux::try_change_repeat()
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        platform::macos::try_change_repeat()
+    }
+}
+
+pub fn set_playback_rate(rate: f64) -> CommandResult {
+    #[cfg(target_os = "windows")]
+    {
+        platform::windows::try_change_playback_rate(rate)
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        platform::linux::try_change_playback_rate(rate)
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        platform::macos::try_change_playback_rate(rate)
+    }
+}
+
+pub fn set_playback_position(position: i64) -> CommandResult {
+    #[cfg(target_os = "windows")]
+    {
+        platform::windows::try_change_playback_position(position)
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        platform::linux::try_change_playback_position(position)
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        platform::macos::try_change_playback_position(position)
+    }
+}
+
+/// Moves the position by `seconds`, which may be negative, without leaving the track.
+pub fn seek(seconds: f64) -> CommandResult {
+    let state = match playback_state() {
+        Ok(state) if state.current_time() >= 0 => state,
+        _ => return CommandResult::NoPlayer,
+    };
+
+    let mut position = state.current_time() + (seconds * time_units_per_second() as f64) as i64;
+    if state.max_time() > 0 {
+        position = position.min(state.max_time());
+    }
+    set_playback_position(position.max(0))
+}
+
+/// `volume` is linear, 0.0 muted and 1.0 full.
+pub fn set_volume(volume: f64) -> CommandResult {
+    #[cfg(target_os = "windows")]
+    {
+        platform::windows::try_set_volume(volume)
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        platform::linux::try_set_volume(volume)
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        platform::macos::try_set_volume(volume)
+    }
+}
+
+pub fn open_uri(uri: &str) -> CommandResult {
+    #[cfg(target_os = "windows")]
+    {
+        platform::windows::try_open_uri(uri)
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        platform::linux::try_open_uri(uri)
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        platform::macos::try_open_uri(uri)
+    }
+}
+
+pub fn supported_uri_schemes() -> Vec<String> {
+    #[cfg(target_os = "windows")]
+    {
+        platform::windows::get_supported_uri_schemes()
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        platform::linux::get_supported_uri_schemes().unwrap_or_default()
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        platform::macos::get_supported_uri_schemes()
+    }
+}
+
+pub fn supported_mime_types() -> Vec<String> {
+    #[cfg(target_os = "windows")]
+    {
+        platform::windows::get_supported_mime_types()
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        platform::linux::get_supported_mime_types().unwrap_or_default()
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        platform::macos::get_supported_mime_types()
+    }
+}
+
+pub fn raise() -> CommandResult {
+    #[cfg(target_os = "windows")]
+    {
+        platform::windows::try_raise()
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        platform::linux::try_raise()
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        platform::macos::try_raise()
+    }
+}
+
+pub fn quit() -> CommandResult {
+    #[cfg(target_os = "windows")]
+    {
+        platform::windows::try_quit()
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        platform::linux::try_quit()
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        platform::macos::try_quit()
+    }
+}
+
+pub fn set_fullscreen(fullscreen: bool) -> CommandResult {
+    #[cfg(target_os = "windows")]
+    {
+        platform::windows::try_set_fullscreen(fullscreen)
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        platform::linux::try_set_fullscreen(fullscreen)
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        platform::macos::try_set_fullscreen(fullscreen)
+    }
+}
+
+pub fn can_raise() -> bool {
+    #[cfg(target_os = "windows")]
+    {
+        platform::windows::can_raise()
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        platform::linux::can_raise()
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        platform::macos::can_raise()
+    }
+}
+
+pub fn can_quit() -> bool {
+    #[cfg(target_os = "windows")]
+    {
+        platform::windows::can_quit()
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        platform::linux::can_quit()
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        platform::macos::can_quit()
+    }
+}
+
+pub fn can_set_fullscreen() -> bool {
+    #[cfg(target_os = "windows")]
+    {
+        platform::windows::can_set_fullscreen()
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        platform::linux::can_set_fullscreen()
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        platform::macos::can_set_fullscreen()
+    }
+}
+
+pub fn has_track_list() -> bool {
+    #[cfg(target_os = "windows")]
+    {
+        platform::windows::has_track_list()
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        platform::linux::has_track_list()
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        platform::macos::has_track_list()
+    }
+}
+
+pub fn track_list() -> Vec<TrackInfo> {
+    #[cfg(target_os = "windows")]
+    {
+        platform::windows::get_track_list()
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        platform::linux::get_track_list().unwrap_or_default()
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        platform::macos::get_track_list()
+    }
+}
+
+pub fn go_to(track_id: &str) -> CommandResult {
+    #[cfg(target_os = "windows")]
+    {
+        platform::windows::try_go_to(track_id)
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        platform::linux::try_go_to(track_id)
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        platform::macos::try_go_to(track_id)
+    }
+}
+
+/// With no `after_track_id` the track goes to the start of the list.
+pub fn add_track(uri: &str, after_track_id: Option<&str>, set_as_current: bool) -> CommandResult {
+    #[cfg(target_os = "windows")]
+    {
+        platform::windows::try_add_track(uri, after_track_id, set_as_current)
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        platform::linux::try_add_track(uri, after_track_id, set_as_current)
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        platform::macos::try_add_track(uri, after_track_id, set_as_current)
+    }
+}
+
+pub fn remove_track(track_id: &str) -> CommandResult {
+    #[cfg(target_os = "windows")]
+    {
+        platform::windows::try_remove_track(track_id)
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        platform::linux::try_remove_track(track_id)
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        platform::macos::try_remove_track(track_id)
+    }
+}
+
+pub fn playlists(index: u32, max_count: u32, order: &str, reverse_order: bool) -> Vec<PlaylistInfo> {
+    #[cfg(target_os = "windows")]
+    {
+        platform::windows::get_playlists(index, max_count, order, reverse_order)
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        platform::linux::get_playlists(index, max_count, order, reverse_order).unwrap_or_default()
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        platform::macos::get_playlists(index, max_count, order, reverse_order)
+    }
+}
+
+pub fn playlist_orderings() -> Vec<String> {
+    #[cfg(target_os = "windows")]
+    {
+        platform::windows::get_playlist_orderings()
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        platform::linux::get_playlist_orderings().unwrap_or_default()
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        platform::macos::get_playlist_orderings()
+    }
+}
+
+pub fn playlist_count() -> u32 {
+    #[cfg(target_os = "windows")]
+    {
+        platform::windows::get_playlist_count()
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        platform::linux::get_playlist_count().unwrap_or(0)
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        platform::macos::get_playlist_count()
+    }
+}
+
+pub fn active_playlist() -> Option<PlaylistInfo> {
+    #[cfg(target_os = "windows")]
+    {
+        platform::windows::get_active_playlist()
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        platform::linux::get_active_playlist().ok().flatten()
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        platform::macos::get_active_playlist()
+    }
+}
+
+pub fn activate_playlist(playlist_id: &str) -> CommandResult {
+    #[cfg(target_os = "windows")]
+    {
+        platform::windows::try_activate_playlist(playlist_id)
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        platform::linux::try_activate_playlist(playlist_id)
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        platform::macos::try_activate_playlist(playlist_id)
+    }
+}
+
+pub(crate) fn percent_decode(text: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut rest = text.as_bytes();
+
+    while let Some((&byte, tail)) = rest.split_first() {
+        if byte == b'%' {
+            let hex = std::str::from_utf8(tail.get(..2)?).ok()?;
+            bytes.push(u8::from_str_radix(hex, 16).ok()?);
+            rest = &tail[2..];
+        } else {
+            bytes.push(byte);
+            rest = tail;
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
@@ -0,0 +1,147 @@
+use {
+    crate::*,
+    jni::{
+        objects::JValue,
+        JNIEnv,
+        NativeMethod
+    },
+    std::ffi::c_void
+};
+
+/// System properties naming the classes to bind, so the Java side can be shaded or relocated.
+const MEDIA_CLASS_PROPERTY: &str = "mediainfo.nativeMediaClass";
+const CONTROLLER_CLASS_PROPERTY: &str = "mediainfo.nativeControllerClass";
+/// The declared type of the listener parameters, which is part of their method signatures.
+const LISTENER_CLASS_PROPERTY: &str = "mediainfo.listenerClass";
+
+const DEFAULT_MEDIA_CLASS: &str = "dev.yuzuki.libs.media.NativeMedia";
+const DEFAULT_CONTROLLER_CLASS: &str = "dev.yuzuki.libs.media.NativeController";
+const DEFAULT_LISTENER_CLASS: &str = "java.lang.Object";
+
+/// Registers the natives against the configured classes. Anything that can't be registered is left
+/// to the JVM's lookup of the exported `Java_dev_yuzuki_libs_media_*` symbols.
+pub(crate) fn register(env: &mut JNIEnv) {
+    let media_class = property(env, MEDIA_CLASS_PROPERTY, DEFAULT_MEDIA_CLASS);
+    let controller_class = property(env, CONTROLLER_CLASS_PROPERTY, DEFAULT_CONTROLLER_CLASS);
+    let listener = format!("L{};", property(env, LISTENER_CLASS_PROPERTY, DEFAULT_LISTENER_CLASS));
+
+    register_class(env, &media_class, media_methods(&listener));
+    register_class(env, &controller_class, controller_methods());
+}
+
+fn register_class(env: &mut JNIEnv, class: &str, methods: Vec<NativeMethod>) {
+    let Ok(class) = env.find_class(class) else {
+        let _ = env.exception_clear();
+        return;
+    };
+
+    // One at a time, so a single signature mismatch doesn't take the whole class down.
+    for method in methods {
+        if env.register_native_methods(&class, &[method]).is_err() {
+            let _ = env.exception_clear();
+        }
+    }
+}
+
+/// Reads a class name property, converted from `a.b.C` to the `a/b/C` form JNI expects.
+fn property(env: &mut JNIEnv, key: &str, default: &str) -> String {
+    let value = env.new_string(key)
+        .and_then(|key| env.call_static_method("java/lang/System", "getProperty", "(Ljava/lang/String;)Ljava/lang/String;", &[JValue::Object(&key)]))
+        .and_then(|value| value.l());
+
+    let value = match value {
+        Ok(value) if !value.is_null() => get_string(env, &value.into()),
+        _ => {
+            let _ = env.exception_clear();
+            None
+        }
+    };
+
+    value.filter(|v| !v.is_empty()).unwrap_or(default.to_string()).replace('.', "/")
+}
+
+fn method(name: &str, sig: &str, fn_ptr: *mut c_void) -> NativeMethod {
+    NativeMethod {
+        name: name.into(),
+        sig: sig.into(),
+        fn_ptr,
+    }
+}
+
+fn media_methods(listener: &str) -> Vec<NativeMethod> {
+    vec![
+        method("init", "(Ljava/lang/String;)Z", Java_dev_yuzuki_libs_media_NativeMedia_init as *mut c_void),
+        method("shutdown", "()V", Java_dev_yuzuki_libs_media_NativeMedia_shutdown as *mut c_void),
+        method("getNativeVersion", "()Ljava/lang/String;", Java_dev_yuzuki_libs_media_NativeMedia_getNativeVersion as *mut c_void),
+        method("getNativeFeatures", "()Ljava/lang/String;", Java_dev_yuzuki_libs_media_NativeMedia_getNativeFeatures as *mut c_void),
+        method("getMediaInfo", "()Ljava/lang/String;", Java_dev_yuzuki_libs_media_NativeMedia_getMediaInfo as *mut c_void),
+        method("getPlaybackState", "()Ljava/lang/String;", Java_dev_yuzuki_libs_media_NativeMedia_getPlaybackState as *mut c_void),
+        method("getPlaybackRate", "()D", Java_dev_yuzuki_libs_media_NativeMedia_getPlaybackRate as *mut c_void),
+        method("getVolume", "()D", Java_dev_yuzuki_libs_media_NativeMedia_getVolume as *mut c_void),
+        method("getSupportedUriSchemes", "()Ljava/lang/String;", Java_dev_yuzuki_libs_media_NativeMedia_getSupportedUriSchemes as *mut c_void),
+        method("getSupportedMimeTypes", "()Ljava/lang/String;", Java_dev_yuzuki_libs_media_NativeMedia_getSupportedMimeTypes as *mut c_void),
+        method("getTrackList", "()[Ljava/lang/String;", Java_dev_yuzuki_libs_media_NativeMedia_getTrackList as *mut c_void),
+        method("getPlaylists", "(IILjava/lang/String;Z)[Ljava/lang/String;", Java_dev_yuzuki_libs_media_NativeMedia_getPlaylists as *mut c_void),
+        method("getPlaylistOrderings", "()Ljava/lang/String;", Java_dev_yuzuki_libs_media_NativeMedia_getPlaylistOrderings as *mut c_void),
+        method("getPlaylistCount", "()I", Java_dev_yuzuki_libs_media_NativeMedia_getPlaylistCount as *mut c_void),
+        method("getActivePlaylist", "()Ljava/lang/String;", Java_dev_yuzuki_libs_media_NativeMedia_getActivePlaylist as *mut c_void),
+        method("addListener", &format!("({})Z", listener), Java_dev_yuzuki_libs_media_NativeMedia_addListener as *mut c_void),
+        method("removeListener", &format!("({})Z", listener), Java_dev_yuzuki_libs_media_NativeMedia_removeListener as *mut c_void),
+        method("setListenerWindow", &format!("({}J)Z", listener), Java_dev_yuzuki_libs_media_NativeMedia_setListenerWindow as *mut c_void),
+        method("setProgressInterval", &format!("({}J)Z", listener), Java_dev_yuzuki_libs_media_NativeMedia_setProgressInterval as *mut c_void),
+        method("setPollingInterval", "(J)V", Java_dev_yuzuki_libs_media_NativeMedia_setPollingInterval as *mut c_void),
+        method("getPolledPlayers", "()Ljava/lang/String;", Java_dev_yuzuki_libs_media_NativeMedia_getPolledPlayers as *mut c_void),
+        method("formatNowPlaying", "(Ljava/lang/String;)Ljava/lang/String;", Java_dev_yuzuki_libs_media_NativeMedia_formatNowPlaying as *mut c_void),
+        #[cfg(feature = "overlay")]
+        method("startOverlay", "(Ljava/lang/String;Ljava/lang/String;J)J", Java_dev_yuzuki_libs_media_NativeMedia_startOverlay as *mut c_void),
+        #[cfg(feature = "overlay")]
+        method("stopOverlay", "(J)Z", Java_dev_yuzuki_libs_media_NativeMedia_stopOverlay as *mut c_void),
+        #[cfg(feature = "server")]
+        method("startServer", "(Ljava/lang/String;Ljava/lang/String;)J", Java_dev_yuzuki_libs_media_NativeMedia_startServer as *mut c_void),
+        #[cfg(feature = "server")]
+        method("stopServer", "(J)Z", Java_dev_yuzuki_libs_media_NativeMedia_stopServer as *mut c_void),
+        #[cfg(all(unix, feature = "rpc"))]
+        method("startRpcServer", "(Ljava/lang/String;)J", Java_dev_yuzuki_libs_media_NativeMedia_startRpcServer as *mut c_void),
+        #[cfg(all(unix, feature = "rpc"))]
+        method("stopRpcServer", "(J)Z", Java_dev_yuzuki_libs_media_NativeMedia_stopRpcServer as *mut c_void),
+        method("getGeneration", "()J", Java_dev_yuzuki_libs_media_NativeMedia_getGeneration as *mut c_void),
+        method("waitForChange", "(JJ)Ljava/lang/String;", Java_dev_yuzuki_libs_media_NativeMedia_waitForChange as *mut c_void),
+        method("addPositionTrigger", "(Ljava/lang/String;J)J", Java_dev_yuzuki_libs_media_NativeMedia_addPositionTrigger as *mut c_void),
+        method("removePositionTrigger", "(J)Z", Java_dev_yuzuki_libs_media_NativeMedia_removePositionTrigger as *mut c_void),
+    ]
+}
+
+fn controller_methods() -> Vec<NativeMethod> {
+    vec![
+        method("play", "()I", Java_dev_yuzuki_libs_media_NativeController_play as *mut c_void),
+        method("pause", "()I", Java_dev_yuzuki_libs_media_NativeController_pause as *mut c_void),
+        method("stop", "()I", Java_dev_yuzuki_libs_media_NativeController_stop as *mut c_void),
+        method("record", "()I", Java_dev_yuzuki_libs_media_NativeController_record as *mut c_void),
+        method("fastForward", "()I", Java_dev_yuzuki_libs_media_NativeController_fastForward as *mut c_void),
+        method("rewind", "()I", Java_dev_yuzuki_libs_media_NativeController_rewind as *mut c_void),
+        method("next", "()I", Java_dev_yuzuki_libs_media_NativeController_next as *mut c_void),
+        method("previous", "()I", Java_dev_yuzuki_libs_media_NativeController_previous as *mut c_void),
+        method("channelUp", "()I", Java_dev_yuzuki_libs_media_NativeController_channelUp as *mut c_void),
+        method("channelDown", "()I", Java_dev_yuzuki_libs_media_NativeController_channelDown as *mut c_void),
+        method("togglePlayPause", "()I", Java_dev_yuzuki_libs_media_NativeController_togglePlayPause as *mut c_void),
+        method("shuffle", "(Z)I", Java_dev_yuzuki_libs_media_NativeController_shuffle as *mut c_void),
+        method("repeat", "()I", Java_dev_yuzuki_libs_media_NativeController_repeat as *mut c_void),
+        method("playbackRate", "(D)I", Java_dev_yuzuki_libs_media_NativeController_playbackRate as *mut c_void),
+        method("playbackPosition", "(J)I", Java_dev_yuzuki_libs_media_NativeController_playbackPosition as *mut c_void),
+        method("volume", "(D)I", Java_dev_yuzuki_libs_media_NativeController_volume as *mut c_void),
+        method("openUri", "(Ljava/lang/String;)I", Java_dev_yuzuki_libs_media_NativeController_openUri as *mut c_void),
+        method("playerAvailable", "()Z", Java_dev_yuzuki_libs_media_NativeController_playerAvailable as *mut c_void),
+        method("raise", "()I", Java_dev_yuzuki_libs_media_NativeController_raise as *mut c_void),
+        method("quit", "()I", Java_dev_yuzuki_libs_media_NativeController_quit as *mut c_void),
+        method("fullscreen", "(Z)I", Java_dev_yuzuki_libs_media_NativeController_fullscreen as *mut c_void),
+        method("canRaise", "()Z", Java_dev_yuzuki_libs_media_NativeController_canRaise as *mut c_void),
+        method("canQuit", "()Z", Java_dev_yuzuki_libs_media_NativeController_canQuit as *mut c_void),
+        method("canSetFullscreen", "()Z", Java_dev_yuzuki_libs_media_NativeController_canSetFullscreen as *mut c_void),
+        method("hasTrackList", "()Z", Java_dev_yuzuki_libs_media_NativeController_hasTrackList as *mut c_void),
+        method("goTo", "(Ljava/lang/String;)I", Java_dev_yuzuki_libs_media_NativeController_goTo as *mut c_void),
+        method("addTrack", "(Ljava/lang/String;Ljava/lang/String;Z)I", Java_dev_yuzuki_libs_media_NativeController_addTrack as *mut c_void),
+        method("removeTrack", "(Ljava/lang/String;)I", Java_dev_yuzuki_libs_media_NativeController_removeTrack as *mut c_void),
+        method("activatePlaylist", "(Ljava/lang/String;)I", Java_dev_yuzuki_libs_media_NativeController_activatePlaylist as *mut c_void),
+        method("getLastError", "()Ljava/lang/String;", Java_dev_yuzuki_libs_media_NativeController_getLastError as *mut c_void),
+    ]
+}
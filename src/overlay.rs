@@ -0,0 +1,138 @@
+//! Keeps a directory of files for streaming software text and image sources up to date: the
+//! now-playing text, a copy of the album art and the snapshot as JSON. Every file is written to a
+//! temporary name next to it and renamed over it, so a reader never sees it half written.
+//!
+//! Only art the player keeps in a local file can be copied. Remote `http(s)` art, which some players
+//! such as Spotify report, isn't downloaded; the art file is removed instead so it never shows the
+//! previous track's cover. Serve `albumArt` from the JSON file to a browser source for those.
+
+use {
+    crate::{
+        event::{self, Event},
+        media,
+        template::Template,
+        Snapshot
+    },
+    std::{
+        fs,
+        io::{self, Write},
+        path::PathBuf,
+        sync::Mutex,
+        time::Duration
+    }
+};
+
+/// Where to write and what. Built with [`Overlay::new`] and the file name setters.
+#[derive(Clone, Debug)]
+pub struct Overlay {
+    directory: PathBuf,
+    template: Template,
+    text_file: String,
+    art_file: String,
+    json_file: String,
+}
+
+struct Running {
+    ids: Vec<i64>,
+    next_id: i64,
+}
+
+static RUNNING: Mutex<Running> = Mutex::new(Running {
+    ids: Vec::new(),
+    next_id: 1,
+});
+
+impl Overlay {
+    pub fn new(directory: impl Into<PathBuf>, template: Template) -> Self {
+        Self {
+            directory: directory.into(),
+            template,
+            text_file: "now-playing.txt".to_string(),
+            art_file: "album-art".to_string(),
+            json_file: "now-playing.json".to_string(),
+        }
+    }
+
+    pub fn text_file(mut self, name: &str) -> Self {
+        self.text_file = name.to_string();
+        self
+    }
+
+    /// The art is copied as is, whatever its format, so the name should leave the type to the reader.
+    pub fn art_file(mut self, name: &str) -> Self {
+        self.art_file = name.to_string();
+        self
+    }
+
+    pub fn json_file(mut self, name: &str) -> Self {
+        self.json_file = name.to_string();
+        self
+    }
+
+    /// Writes the text and JSON files for `snapshot` and copies its art. Art that isn't a local
+    /// file is left out and the previous copy removed.
+    pub fn write(&self, snapshot: &Snapshot) -> io::Result<()> {
+        fs::create_dir_all(&self.directory)?;
+
+        self.replace(&self.text_file, self.template.render(snapshot, None).as_bytes())?;
+        self.replace(&self.json_file, serde_json::to_string_pretty(snapshot)?.as_bytes())?;
+
+        match media::album_art_file(snapshot.media_info()).map(fs::read) {
+            // Players may reuse one cache file for every cover, so compare contents rather than paths.
+            Some(Ok(bytes)) if fs::read(self.directory.join(&self.art_file)).is_ok_and(|copy| copy == bytes) => Ok(()),
+            Some(Ok(bytes)) => self.replace(&self.art_file, &bytes),
+            _ => match fs::remove_file(self.directory.join(&self.art_file)) {
+                Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+                _ => Ok(()),
+            },
+        }
+    }
+
+    /// Writes the files now and again after every change, coalescing changes within `window`.
+    /// Returns an id for [`stop`].
+    pub fn start(self, window: Duration) -> i64 {
+        let id = {
+            let mut running = RUNNING.lock().unwrap();
+            let id = running.next_id;
+            running.next_id += 1;
+            running.ids.push(id);
+            id
+        };
+
+        event::subscribe_coalesced(window, move |event| {
+            if !RUNNING.lock().unwrap().ids.contains(&id) {
+                return false;
+            }
+            if let Event::Changed(snapshot) = event {
+                // A failed write is retried with the next change; the files keep their last contents.
+                let _ = self.write(&snapshot);
+            }
+            true
+        });
+        id
+    }
+
+    fn replace(&self, name: &str, contents: &[u8]) -> io::Result<()> {
+        let path = self.directory.join(name);
+        let temporary = self.directory.join(format!(".{}.tmp", name));
+
+        let mut file = fs::File::create(&temporary)?;
+        file.write_all(contents)?;
+        file.sync_all()?;
+        drop(file);
+
+        fs::rename(&temporary, &path)
+    }
+}
+
+/// Stops an overlay started with [`Overlay::start`]; it finishes once the next change arrives.
+pub fn stop(id: i64) -> bool {
+    let mut running = RUNNING.lock().unwrap();
+    let before = running.ids.len();
+    running.ids.retain(|&i| i != id);
+    running.ids.len() != before
+}
+
+pub(crate) fn shutdown() {
+    RUNNING.lock().unwrap().ids.clear();
+}
@@ -1,34 +1,101 @@
 #[cfg(target_os = "linux")]
 use {
     anyhow::Result,
-    crate::{MediaInfo, PlaybackState},
-    mpris::{LoopStatus, PlaybackStatus, PlayerFinder},
-    std::time::Duration,
+    crate::{event::{self, Event}, poller, position, CommandResult, MediaInfo, PlaybackState, PlaylistInfo, TrackInfo},
+    dbus::{
+        arg::{AppendAll, Variant},
+        blocking::{BlockingSender, Connection},
+        message::MatchRule,
+        Message,
+        Path
+    },
+    mpris::{DBusError, FindingError, LoopStatus, Metadata, MetadataValue, PlaybackStatus, Player, PlayerFinder, TrackID},
+    std::{
+        cell::RefCell,
+        collections::{BTreeMap, HashMap},
+        fmt::Display,
+        sync::{mpsc::Sender, Mutex},
+        time::Duration
+    },
 };
 
+#[cfg(target_os = "linux")]
+const MPRIS_PATH: &str = "/org/mpris/MediaPlayer2";
+
+#[cfg(target_os = "linux")]
+const MPRIS_PLAYER_INTERFACE: &str = "org.mpris.MediaPlayer2.Player";
+
+#[cfg(target_os = "linux")]
+const MPRIS_TRACK_LIST_INTERFACE: &str = "org.mpris.MediaPlayer2.TrackList";
+
+#[cfg(target_os = "linux")]
+const MPRIS_PLAYLISTS_INTERFACE: &str = "org.mpris.MediaPlayer2.Playlists";
+
+#[cfg(target_os = "linux")]
+const DBUS_PROPERTIES_INTERFACE: &str = "org.freedesktop.DBus.Properties";
+
+#[cfg(target_os = "linux")]
+const MPRIS_BUS_NAME_PREFIX: &str = "org.mpris.MediaPlayer2.";
+
+#[cfg(target_os = "linux")]
+const DBUS_BUS_NAME: &str = "org.freedesktop.DBus";
+
+#[cfg(target_os = "linux")]
+const DBUS_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Identity of every MPRIS player seen on the bus, keyed by bus name.
+///
+/// Kept in sync with `NameOwnerChanged` so a player's Identity is still known after it vanished.
+#[cfg(target_os = "linux")]
+static PLAYERS: Mutex<BTreeMap<String, String>> = Mutex::new(BTreeMap::new());
+
+/// Bus name or identity of the player to use instead of the active one, set by `select_player`.
+#[cfg(target_os = "linux")]
+static SELECTED: Mutex<Option<String>> = Mutex::new(None);
+
+/// Unique bus name of the player the position cache was last filled from, so it can be cleared when
+/// that player goes away.
+#[cfg(target_os = "linux")]
+static POSITION_PLAYER: Mutex<Option<String>> = Mutex::new(None);
+
+#[cfg(target_os = "linux")]
+thread_local! {
+    /// Player for this thread's calls only, set by `with_player`. Wins over `SELECTED`.
+    static THREAD_PLAYER: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
 #[cfg(target_os = "linux")]
 pub fn get_media_info() -> Result<MediaInfo> {
     let player_finder = PlayerFinder::new()?;
-    let player = player_finder.find_active()?;
+    let player = find_player(&player_finder)?;
     let metadata = player.get_metadata()?;
 
-    let media_info = MediaInfo::new(
-        metadata.title().unwrap_or("Unavailable").to_string(),
-        vec_to_str(metadata.artists().unwrap()),
-        metadata.album_name().unwrap_or("Unavailable").to_string(),
-        metadata.art_url().unwrap_or("Unavailable").to_string(),
-    );
-
-    Ok(media_info)
+    Ok(to_media_info(&metadata))
 }
 
 #[cfg(target_os = "linux")]
 pub fn get_playback_state() -> Result<PlaybackState> {
     let player_finder = PlayerFinder::new()?;
-    let player = player_finder.find_active()?;
+    let player = find_player(&player_finder)?;
 
     let metadata = player.get_metadata()?;
 
+    // The cache follows the selected or active player, not one picked for a single thread's calls.
+    let cached = THREAD_PLAYER.with(|p| p.borrow().is_none());
+
+    // Some players can't report Position; fall back to interpolating the last known one.
+    let position = match player.get_position_in_microseconds() {
+        Ok(position) => {
+            let position = i64::try_from(position)?;
+            if cached {
+                position::update(position, player.get_playback_rate().unwrap_or(1.0), player.get_playback_status()? == PlaybackStatus::Playing);
+                *POSITION_PLAYER.lock().unwrap() = Some(player.unique_name().to_string());
+            }
+            position
+        }
+        Err(e) => position::current().filter(|_| cached).ok_or(e)?,
+    };
+
     let playback_state = PlaybackState::new(
         player.get_playback_status()? == PlaybackStatus::Playing,
         player.get_playback_status()? == PlaybackStatus::Paused,
@@ -36,7 +103,7 @@ pub fn get_playback_state() -> Result<PlaybackState> {
         if player.can_shuffle()? { player.get_shuffle()? } else { false },
         if player.can_loop()? { player.get_loop_status()? == LoopStatus::Track } else { false },
         if player.can_loop()? { player.get_loop_status()? == LoopStatus::Playlist } else { false },
-        player.get_position_in_microseconds()?.try_into()?,
+        position,
         i64::try_from(metadata.length_in_microseconds().unwrap())?,
         player.can_play()?,
         player.can_pause()?,
@@ -52,143 +119,533 @@ pub fn get_playback_state() -> Result<PlaybackState> {
         player.can_shuffle()?,
         player.can_loop()?,
         player.has_playback_rate()?,
-        player.has_position()?
+        player.has_position()?,
+    ).with_rate_and_volume(
+        player.get_playback_rate().unwrap_or(1.0),
+        player.get_volume().unwrap_or(-1.0),
     );
 
     Ok(playback_state)
 }
 
 #[cfg(target_os = "linux")]
-pub fn try_play() -> bool {
-    PlayerFinder::new()
-        .ok()
-        .and_then(|f| f.find_active().ok())
-        .and_then(|f| f.play().ok())
-        .is_some()
+pub fn try_play() -> CommandResult {
+    command(Player::can_play, Player::play)
 }
 
 #[cfg(target_os = "linux")]
-pub fn try_pause() -> bool {
-    PlayerFinder::new()
-        .ok()
-        .and_then(|f| f.find_active().ok())
-        .and_then(|f| f.pause().ok())
-        .is_some()
+pub fn try_pause() -> CommandResult {
+    command(Player::can_pause, Player::pause)
 }
 
 #[cfg(target_os = "linux")]
-pub fn try_stop() -> bool {
-    PlayerFinder::new()
-        .ok()
-        .and_then(|f| f.find_active().ok())
-        .and_then(|f| f.stop().ok())
-        .is_some()
+pub fn try_stop() -> CommandResult {
+    command(Player::can_stop, Player::stop)
 }
 
 #[cfg(target_os = "linux")]
-pub fn try_record() -> bool {
-    false
+pub fn try_record() -> CommandResult {
+    CommandResult::NotSupported //because linux hasn't this method.
 }
 
 #[cfg(target_os = "linux")]
-pub fn try_fast_forward() -> bool {
-    false
+pub fn try_fast_forward() -> CommandResult {
+    CommandResult::NotSupported //because linux hasn't this method.
 }
 
 #[cfg(target_os = "linux")]
-pub fn try_rewind() -> bool {
-    false
+pub fn try_rewind() -> CommandResult {
+    CommandResult::NotSupported //because linux hasn't this method.
 }
 
 #[cfg(target_os = "linux")]
-pub fn try_next() -> bool {
-    PlayerFinder::new()
-        .ok()
-        .and_then(|f| f.find_active().ok())
-        .and_then(|f| f.next().ok())
-        .is_some()
+pub fn try_next() -> CommandResult {
+    command(Player::can_go_next, Player::next)
 }
 
 #[cfg(target_os = "linux")]
-pub fn try_previous() -> bool {
-    PlayerFinder::new()
-        .ok()
-        .and_then(|f| f.find_active().ok())
-        .and_then(|f| f.previous().ok())
-        .is_some()
+pub fn try_previous() -> CommandResult {
+    command(Player::can_go_previous, Player::previous)
+}
+
+#[cfg(target_os = "linux")]
+pub fn try_change_channel_up() -> CommandResult {
+    CommandResult::NotSupported //because linux hasn't this method.
+}
+
+#[cfg(target_os = "linux")]
+pub fn try_change_channel_down() -> CommandResult {
+    CommandResult::NotSupported //because linux hasn't this method.
 }
 
 #[cfg(target_os = "linux")]
-pub fn try_change_channel_up() -> bool {
-    false
+pub fn try_play_pause_toggle() -> CommandResult {
+    command(Player::can_pause, Player::play_pause)
 }
 
 #[cfg(target_os = "linux")]
-pub fn try_change_channel_down() -> bool {
-    false
+pub fn try_change_shuffle(shuffle: bool) -> CommandResult {
+    command(Player::can_shuffle, |p| p.set_shuffle(shuffle))
 }
 
 #[cfg(target_os = "linux")]
-pub fn try_play_pause_toggle() -> bool {
+pub fn try_change_repeat() -> CommandResult {
+    command(Player::can_loop, |p| p.set_loop_status(next(p.get_loop_status()?)))
+}
+
+#[cfg(target_os = "linux")]
+pub fn try_change_playback_rate(i: f64) -> CommandResult {
+    command(Player::can_set_playback_rate, |p| p.set_playback_rate(i))
+}
+
+#[cfg(target_os = "linux")]
+pub fn try_change_playback_position(i: i64) -> CommandResult {
+    command(Player::can_seek, |p| -> Result<()> {
+        let track_id = p.get_metadata()?.track_id().ok_or(anyhow::anyhow!("current track has no track id"))?;
+        Ok(p.set_position(track_id, &Duration::from_micros(i as u64))?)
+    })
+}
+
+#[cfg(target_os = "linux")]
+pub fn try_set_volume(volume: f64) -> CommandResult {
+    command(Player::can_control, |p| p.set_volume(volume))
+}
+
+#[cfg(target_os = "linux")]
+pub fn try_open_uri(uri: &str) -> CommandResult {
+    // mpris doesn't expose OpenUri, so call it on the player's bus name directly.
+    command(always, |p| call(p, MPRIS_PLAYER_INTERFACE, "OpenUri", (uri,)).map(|_| ()))
+}
+
+#[cfg(target_os = "linux")]
+pub fn get_supported_uri_schemes() -> Result<Vec<String>> {
+    let player_finder = PlayerFinder::new()?;
+    let player = find_player(&player_finder)?;
+
+    Ok(player.get_supported_uri_schemes()?)
+}
+
+#[cfg(target_os = "linux")]
+pub fn get_supported_mime_types() -> Result<Vec<String>> {
+    let player_finder = PlayerFinder::new()?;
+    let player = find_player(&player_finder)?;
+
+    Ok(player.get_supported_mime_types()?)
+}
+
+#[cfg(target_os = "linux")]
+pub fn try_raise() -> CommandResult {
+    command(Player::can_raise, Player::raise)
+}
+
+#[cfg(target_os = "linux")]
+pub fn try_quit() -> CommandResult {
+    command(Player::can_quit, Player::quit)
+}
+
+#[cfg(target_os = "linux")]
+pub fn try_set_fullscreen(fullscreen: bool) -> CommandResult {
+    command(Player::can_set_fullscreen, |p| p.set_fullscreen(fullscreen).map(|_| ()))
+}
+
+#[cfg(target_os = "linux")]
+pub fn can_raise() -> bool {
     PlayerFinder::new()
         .ok()
-        .and_then(|f| f.find_active().ok())
-        .and_then(|f| f.play_pause().ok())
-        .is_some()
+        .and_then(|f| find_player(&f).ok())
+        .and_then(|p| p.can_raise().ok())
+        .unwrap_or(false)
 }
 
 #[cfg(target_os = "linux")]
-pub fn try_change_shuffle() -> bool {
+pub fn can_quit() -> bool {
     PlayerFinder::new()
         .ok()
-        .and_then(|f| f.find_active().ok())
-        .and_then(|p| {
-            p.set_shuffle(
-                !PlayerFinder::new()
-                    .ok()
-                    .and_then(|f| f.find_active().ok())
-                    .and_then(|p| p.get_shuffle().ok())
-                    .unwrap()
-            ).ok()
-        }).is_some()
+        .and_then(|f| find_player(&f).ok())
+        .and_then(|p| p.can_quit().ok())
+        .unwrap_or(false)
 }
 
 #[cfg(target_os = "linux")]
-pub fn try_change_repeat() -> bool {
+pub fn can_set_fullscreen() -> bool {
     PlayerFinder::new()
         .ok()
-        .and_then(|f| f.find_active().ok())
-        .and_then(|p| p.set_loop_status(next(p.get_loop_status().unwrap())).ok())
-        .is_some()
+        .and_then(|f| find_player(&f).ok())
+        .and_then(|p| p.can_set_fullscreen().ok())
+        .unwrap_or(false)
 }
 
 #[cfg(target_os = "linux")]
-pub fn try_change_playback_rate(i: f64) -> bool {
+pub fn has_track_list() -> bool {
     PlayerFinder::new()
         .ok()
-        .and_then(|f| f.find_active().ok())
-        .and_then(|f| f.set_playback_rate(i).ok())
-        .is_some()
+        .and_then(|f| find_player(&f).ok())
+        .and_then(|p| p.get_has_track_list().ok())
+        .unwrap_or(false)
+}
+
+#[cfg(target_os = "linux")]
+pub fn get_track_list() -> Result<Vec<TrackInfo>> {
+    let player_finder = PlayerFinder::new()?;
+    let player = find_player(&player_finder)?;
+
+    let track_list = player.get_track_list()?;
+    // Players may leave entries out of the reply or reorder them, so match them up by id.
+    let metadata: HashMap<String, Metadata> = player.get_tracks_metadata(track_list.ids())?
+        .into_iter()
+        .filter_map(|m| Some((m.track_id()?.to_string(), m)))
+        .collect();
+
+    let tracks = track_list.ids()
+        .iter()
+        .map(|id| {
+            let id = id.to_string();
+            let media_info = metadata.get(&id).map_or(MediaInfo::empty(), to_media_info);
+            TrackInfo::new(id, media_info)
+        })
+        .collect();
+
+    Ok(tracks)
+}
+
+#[cfg(target_os = "linux")]
+pub fn try_go_to(track_id: &str) -> CommandResult {
+    command(Player::get_has_track_list, |p| -> Result<()> {
+        Ok(p.go_to(&TrackID::new(track_id).map_err(anyhow::Error::msg)?)?)
+    })
+}
+
+#[cfg(target_os = "linux")]
+pub fn try_add_track(uri: &str, after_track_id: Option<&str>, set_as_current: bool) -> CommandResult {
+    command(Player::can_edit_tracks, |p| -> Result<()> {
+        match after_track_id {
+            Some(id) => Ok(p.add_track(uri, &TrackID::new(id).map_err(anyhow::Error::msg)?, set_as_current)?),
+            None => Ok(p.add_track_at_start(uri, set_as_current)?),
+        }
+    })
 }
 
 #[cfg(target_os = "linux")]
-pub fn try_change_playback_position(i: i64) -> bool {
+pub fn try_remove_track(track_id: &str) -> CommandResult {
+    command(Player::can_edit_tracks, |p| -> Result<()> {
+        Ok(p.remove_track(&TrackID::new(track_id).map_err(anyhow::Error::msg)?)?)
+    })
+}
+
+#[cfg(target_os = "linux")]
+pub fn get_playlists(index: u32, max_count: u32, order: &str, reverse_order: bool) -> Result<Vec<PlaylistInfo>> {
+    let reply = call_active(MPRIS_PLAYLISTS_INTERFACE, "GetPlaylists", (index, max_count, order, reverse_order))?;
+    let playlists: Vec<(Path, String, String)> = reply.read1()?;
+
+    Ok(playlists.iter().map(to_playlist_info).collect())
+}
+
+#[cfg(target_os = "linux")]
+pub fn get_playlist_orderings() -> Result<Vec<String>> {
+    let reply = call_active(DBUS_PROPERTIES_INTERFACE, "Get", (MPRIS_PLAYLISTS_INTERFACE, "Orderings"))?;
+    let orderings: Variant<Vec<String>> = reply.read1()?;
+
+    Ok(orderings.0)
+}
+
+#[cfg(target_os = "linux")]
+pub fn get_playlist_count() -> Result<u32> {
+    let reply = call_active(DBUS_PROPERTIES_INTERFACE, "Get", (MPRIS_PLAYLISTS_INTERFACE, "PlaylistCount"))?;
+    let count: Variant<u32> = reply.read1()?;
+
+    Ok(count.0)
+}
+
+#[cfg(target_os = "linux")]
+pub fn get_active_playlist() -> Result<Option<PlaylistInfo>> {
+    let reply = call_active(DBUS_PROPERTIES_INTERFACE, "Get", (MPRIS_PLAYLISTS_INTERFACE, "ActivePlaylist"))?;
+    let active: Variant<(bool, (Path, String, String))> = reply.read1()?;
+    let (valid, playlist) = active.0;
+
+    Ok(valid.then(|| to_playlist_info(&playlist)))
+}
+
+#[cfg(target_os = "linux")]
+pub fn try_activate_playlist(playlist_id: &str) -> CommandResult {
+    command(always, |p| -> Result<()> {
+        let playlist_id = Path::new(playlist_id).map_err(anyhow::Error::msg)?;
+        call(p, MPRIS_PLAYLISTS_INTERFACE, "ActivatePlaylist", (playlist_id,)).map(|_| ())
+    })
+}
+
+/// Forwards MPRIS signals from every player on the session bus to `sender` on a background thread.
+#[cfg(target_os = "linux")]
+pub fn spawn_signal_watcher(sender: Sender<Event>) {
+    let epoch = event::epoch();
+    event::spawn(move || {
+        let _ = watch_signals(sender, epoch);
+    });
+}
+
+#[cfg(target_os = "linux")]
+fn watch_signals(sender: Sender<Event>, epoch: u64) -> Result<()> {
+    let connection = Connection::new_session()?;
+
+    if let Ok(players) = PlayerFinder::new()?.find_all() {
+        let mut cache = PLAYERS.lock().unwrap();
+        for player in players {
+            cache.insert(player.bus_name().to_string(), player.identity().to_string());
+        }
+    }
+
+    let tx = sender.clone();
+    connection.add_match(MatchRule::new_signal(DBUS_BUS_NAME, "NameOwnerChanged").with_sender(DBUS_BUS_NAME), move |_: (), _, msg: &Message| {
+        let Ok((name, old_owner, new_owner)) = msg.read3::<&str, &str, &str>() else {
+            return true;
+        };
+        if !name.starts_with(MPRIS_BUS_NAME_PREFIX) {
+            return true;
+        }
+
+        if new_owner.is_empty() {
+            // Signals and polling go by unique name, which is the old owner.
+            poller::forget(old_owner);
+            let mut position_player = POSITION_PLAYER.lock().unwrap();
+            if position_player.as_deref() == Some(old_owner) {
+                *position_player = None;
+                position::clear();
+            }
+            drop(position_player);
+
+            let identity = PLAYERS.lock().unwrap().remove(name).unwrap_or_default();
+            tx.send(Event::PlayerRemoved(name.to_string(), identity)).is_ok()
+        } else if old_owner.is_empty() {
+            let identity = get_identity(name).unwrap_or_default();
+            PLAYERS.lock().unwrap().insert(name.to_string(), identity.clone());
+            tx.send(Event::PlayerAdded(name.to_string(), identity)).is_ok()
+        } else {
+            true
+        }
+    })?;
+
+    let tx = sender.clone();
+    connection.add_match(signal_rule(MPRIS_TRACK_LIST_INTERFACE, "TrackListReplaced"), move |_: (), _, msg: &Message| {
+        match msg.read2::<Vec<Path>, Path>() {
+            Ok((tracks, current)) => tx.send(Event::TrackListReplaced(
+                tracks.iter().map(|t| t.to_string()).collect(),
+                current.to_string(),
+            )).is_ok(),
+            Err(_) => true,
+        }
+    })?;
+
+    let tx = sender.clone();
+    connection.add_match(signal_rule(MPRIS_TRACK_LIST_INTERFACE, "TrackAdded"), move |_: (), _, msg: &Message| {
+        match msg.read2::<HashMap<String, MetadataValue>, Path>() {
+            Ok((metadata, after)) => {
+                let metadata = Metadata::from(metadata);
+                let track_id = metadata.track_id().map(|id| id.to_string()).unwrap_or_default();
+                tx.send(Event::TrackAdded(TrackInfo::new(track_id, to_media_info(&metadata)), after.to_string())).is_ok()
+            }
+            Err(_) => true,
+        }
+    })?;
+
+    let tx = sender.clone();
+    connection.add_match(signal_rule(MPRIS_TRACK_LIST_INTERFACE, "TrackRemoved"), move |_: (), _, msg: &Message| {
+        match msg.read1::<Path>() {
+            Ok(track) => tx.send(Event::TrackRemoved(track.to_string())).is_ok(),
+            Err(_) => true,
+        }
+    })?;
+
+    let tx = sender.clone();
+    connection.add_match(signal_rule(DBUS_PROPERTIES_INTERFACE, "PropertiesChanged"), move |_: (), _, msg: &Message| {
+        match msg.read1::<&str>() {
+            Ok(MPRIS_PLAYER_INTERFACE) => {
+                if let Some(player) = msg.sender() {
+                    poller::signaled(&player);
+                }
+                tx.send(Event::PropertiesChanged).is_ok()
+            }
+            _ => true,
+        }
+    })?;
+
+    let tx = sender.clone();
+    connection.add_match(signal_rule(MPRIS_PLAYER_INTERFACE, "Seeked"), move |_: (), _, msg: &Message| {
+        match msg.read1::<i64>() {
+            // The position cache and the events follow the active player; others seek on their own.
+            Ok(micros) if msg.sender().is_some_and(|sender| active_player_name().as_deref() == Some(&*sender)) => {
+                position::seeked(micros);
+                tx.send(Event::Seeked(micros)).is_ok()
+            }
+            Ok(_) => true,
+            Err(_) => true,
+        }
+    })?;
+
+    let tx = sender;
+    connection.add_match(signal_rule(MPRIS_PLAYLISTS_INTERFACE, "PlaylistChanged"), move |_: (), _, msg: &Message| {
+        match msg.read1::<(Path, String, String)>() {
+            Ok(playlist) => tx.send(Event::PlaylistChanged(to_playlist_info(&playlist))).is_ok(),
+            Err(_) => true,
+        }
+    })?;
+
+    while event::running(epoch) {
+        connection.process(Duration::from_millis(1000))?;
+    }
+    Ok(())
+}
+
+/// Bus name and identity of every MPRIS player on the session bus.
+#[cfg(target_os = "linux")]
+pub fn get_players() -> Result<Vec<(String, String)>> {
+    let players = PlayerFinder::new()?.find_all()?;
+    Ok(players.iter().map(|p| (p.bus_name().to_string(), p.identity().to_string())).collect())
+}
+
+/// Makes every call go to the player matching `name` instead of the active one, or back to the
+/// active one for `None`.
+#[cfg(target_os = "linux")]
+pub fn select_player(name: Option<&str>) {
+    *SELECTED.lock().unwrap() = name.map(str::to_string);
+}
+
+/// Runs `f` with this thread's calls going to the player `name`, matched like `select_player`.
+#[cfg(target_os = "linux")]
+pub fn with_player<T>(name: &str, f: impl FnOnce() -> T) -> T {
+    let previous = THREAD_PLAYER.with(|p| p.replace(Some(name.to_string())));
+    let result = f();
+    THREAD_PLAYER.with(|p| *p.borrow_mut() = previous);
+    result
+}
+
+/// The thread's or selected player, or the active one when none is. `name` matches the full bus
+/// name, the part after `org.mpris.MediaPlayer2.` with or without an instance suffix, or the identity.
+#[cfg(target_os = "linux")]
+fn find_player(finder: &PlayerFinder) -> std::result::Result<Player, FindingError> {
+    let name = THREAD_PLAYER.with(|p| p.borrow().clone()).or_else(|| SELECTED.lock().unwrap().clone());
+    let Some(name) = name else {
+        return finder.find_active();
+    };
+
+    finder.find_all()?
+        .into_iter()
+        .find(|p| {
+            let short = p.bus_name().trim_start_matches(MPRIS_BUS_NAME_PREFIX);
+            p.bus_name() == name
+                || short == name
+                || short.split_once('.').is_some_and(|(base, _)| base == name)
+                || p.identity().eq_ignore_ascii_case(&name)
+        })
+        .ok_or(FindingError::NoPlayerFound)
+}
+
+/// Unique bus name of the active player, which is what signals carry as their sender.
+#[cfg(target_os = "linux")]
+pub fn active_player_name() -> Option<String> {
     PlayerFinder::new()
         .ok()
-        .and_then(|f| f.find_active().ok())
-        .and_then(|p| p.set_position(
-            PlayerFinder::new().ok().and_then(|f| f.find_active().ok()).unwrap().get_metadata().unwrap().track_id().unwrap(),
-            &Duration::from_micros(i as u64)
-        ).ok())
-        .is_some()
+        .and_then(|f| find_player(&f).ok())
+        .map(|p| p.unique_name().to_string())
 }
 
-
 #[cfg(target_os = "linux")]
 pub fn playable() -> bool {
     PlayerFinder::new().ok().is_some()
 }
 
+#[cfg(target_os = "linux")]
+fn to_media_info(metadata: &Metadata) -> MediaInfo {
+    MediaInfo::new(
+        metadata.title().unwrap_or("Unavailable").to_string(),
+        metadata.artists().map(vec_to_str).unwrap_or("Unavailable".to_string()),
+        metadata.album_name().unwrap_or("Unavailable").to_string(),
+        art_path(metadata.art_url()),
+    )
+}
+
+#[cfg(target_os = "linux")]
+fn to_playlist_info((id, name, icon): &(Path, String, String)) -> PlaylistInfo {
+    PlaylistInfo::new(
+        id.to_string(),
+        name.clone(),
+        art_path(Some(icon.as_str()).filter(|icon| !icon.is_empty())),
+    )
+}
+
+/// Resolves an MPRIS art URI the same way for track art and playlist icons.
+#[cfg(target_os = "linux")]
+fn art_path(url: Option<&str>) -> String {
+    url.unwrap_or("Unavailable").to_string()
+}
+
+#[cfg(target_os = "linux")]
+fn get_identity(bus_name: &str) -> Result<String> {
+    let message = Message::new_method_call(bus_name, MPRIS_PATH, DBUS_PROPERTIES_INTERFACE, "Get")
+        .map_err(anyhow::Error::msg)?
+        .append2("org.mpris.MediaPlayer2", "Identity");
+
+    let connection = Connection::new_session()?;
+    let reply = connection.send_with_reply_and_block(message, DBUS_TIMEOUT)?;
+    let identity: Variant<String> = reply.read1()?;
+
+    Ok(identity.0)
+}
+
+/// Calls `member` on the active player and returns the raw reply for the caller to read.
+#[cfg(target_os = "linux")]
+fn call_active(interface: &str, member: &str, args: impl AppendAll) -> Result<Message> {
+    let player_finder = PlayerFinder::new()?;
+    let player = find_player(&player_finder)?;
+
+    call(&player, interface, member, args)
+}
+
+#[cfg(target_os = "linux")]
+fn call(player: &Player, interface: &str, member: &str, args: impl AppendAll) -> Result<Message> {
+    let mut message = Message::new_method_call(player.bus_name(), MPRIS_PATH, interface, member).map_err(anyhow::Error::msg)?;
+    message.append_all(args);
+
+    let connection = Connection::new_session()?;
+    Ok(connection.send_with_reply_and_block(message, DBUS_TIMEOUT)?)
+}
+
+/// Runs `action` on the active player if `capability` allows it, telling apart why it didn't.
+#[cfg(target_os = "linux")]
+fn command<C, A, E1, E2>(capability: C, action: A) -> CommandResult
+where
+    C: FnOnce(&Player) -> std::result::Result<bool, E1>,
+    A: FnOnce(&Player) -> std::result::Result<(), E2>,
+    E1: Display,
+    E2: Display,
+{
+    let player = match PlayerFinder::new().map(|f| find_player(&f)) {
+        Ok(Ok(player)) => player,
+        Ok(Err(FindingError::NoPlayerFound)) => return CommandResult::NoPlayer,
+        Ok(Err(e)) => return CommandResult::Failed(e.to_string()),
+        Err(e) => return CommandResult::Failed(e.to_string()),
+    };
+
+    match capability(&player) {
+        Ok(true) => {}
+        Ok(false) => return CommandResult::CapabilityDisabled,
+        Err(e) => return CommandResult::Failed(e.to_string()),
+    }
+
+    match action(&player) {
+        Ok(()) => CommandResult::Ok,
+        Err(e) => CommandResult::Failed(e.to_string()),
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn always(_: &Player) -> std::result::Result<bool, DBusError> {
+    Ok(true)
+}
+
+#[cfg(target_os = "linux")]
+fn signal_rule(interface: &'static str, member: &'static str) -> MatchRule<'static> {
+    MatchRule::new_signal(interface, member).with_path(MPRIS_PATH)
+}
+
 #[cfg(target_os = "linux")]
 fn vec_to_str(str: Vec<&str>) -> String {
     str.iter()
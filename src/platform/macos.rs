@@ -1,8 +1,12 @@
 #[cfg(target_os = "macos")]
 use {
     crate::{
+        position,
+        CommandResult,
         MediaInfo,
-        PlaybackState
+        PlaybackState,
+        PlaylistInfo,
+        TrackInfo
     },
     objc2_media_player::{
       MPMediaPlayback,
@@ -41,7 +45,7 @@ pub fn get_playback_state() -> PlaybackState {
         let media_item = MPMusicPlayerController::systemMusicPlayer().nowPlayingItem().unwrap();
         let prepared = MPMusicPlayerController::systemMusicPlayer().isPreparedToPlay();
 
-        PlaybackState::new(
+        let playback_state = PlaybackState::new(
             state == MPMusicPlaybackState::Playing,
             state == MPMusicPlaybackState::Paused,
             state == MPMusicPlaybackState::Stopped,
@@ -65,77 +69,84 @@ pub fn get_playback_state() -> PlaybackState {
             prepared,
             true,
             true,
-        )
+        ).with_rate_and_volume(
+            MPMusicPlayerController::systemMusicPlayer().currentPlaybackRate() as f64,
+            -1.0, //because macos hasn't this method.
+        );
+
+        // Keeps the interpolated position that progress ticks, triggers and seek detection read.
+        position::update(playback_state.current_time, playback_state.playback_rate, playback_state.is_playing);
+        playback_state
     }
 }
 
 #[cfg(target_os = "macos")]
-pub fn try_play() -> bool {
+pub fn try_play() -> CommandResult {
     unsafe {
         MPMusicPlayerController::systemMusicPlayer().play();
-        true
+        CommandResult::Ok
     }
 }
 
 #[cfg(target_os = "macos")]
-pub fn try_pause() -> bool {
+pub fn try_pause() -> CommandResult {
     unsafe {
         MPMusicPlayerController::systemMusicPlayer().pause();
-        true
+        CommandResult::Ok
     }
 }
 
 #[cfg(target_os = "macos")]
-pub fn try_stop() -> bool {
+pub fn try_stop() -> CommandResult {
     unsafe {
         MPMusicPlayerController::systemMusicPlayer().stop();
-        true
+        CommandResult::Ok
     }
 }
 
 #[cfg(target_os = "macos")]
-pub fn try_record() -> bool {
-    true
+pub fn try_record() -> CommandResult {
+    CommandResult::NotSupported //because macos hasn't this method.
 }
 
 #[cfg(target_os = "macos")]
-pub fn try_fast_forward() -> bool {
-    true
+pub fn try_fast_forward() -> CommandResult {
+    CommandResult::NotSupported //because macos hasn't this method.
 }
 
 #[cfg(target_os = "macos")]
-pub fn try_rewind() -> bool {
-    true
+pub fn try_rewind() -> CommandResult {
+    CommandResult::NotSupported //because macos hasn't this method.
 }
 
 #[cfg(target_os = "macos")]
-pub fn try_next() -> bool {
+pub fn try_next() -> CommandResult {
     unsafe {
         MPMusicPlayerController::systemMusicPlayer().skipToNextItem();
-        true
+        CommandResult::Ok
     }
 }
 
 #[cfg(target_os = "macos")]
-pub fn try_previous() -> bool {
+pub fn try_previous() -> CommandResult {
     unsafe {
         MPMusicPlayerController::systemMusicPlayer().skipToPreviousItem();
-        true
+        CommandResult::Ok
     }
 }
 
 #[cfg(target_os = "macos")]
-pub fn try_change_channel_up() -> bool {
-    true
+pub fn try_change_channel_up() -> CommandResult {
+    CommandResult::NotSupported //because macos hasn't this method.
 }
 
 #[cfg(target_os = "macos")]
-pub fn try_change_channel_down() -> bool {
-    true
+pub fn try_change_channel_down() -> CommandResult {
+    CommandResult::NotSupported //because macos hasn't this method.
 }
 
 #[cfg(target_os = "macos")]
-pub fn try_play_pause_toggle() -> bool {
+pub fn try_play_pause_toggle() -> CommandResult {
     unsafe {
         let playing = MPMusicPlayerController::systemMusicPlayer().playbackState() == MPMusicPlaybackState::Playing;
         let pausing = MPMusicPlayerController::systemMusicPlayer().playbackState() == MPMusicPlaybackState::Paused;
@@ -144,55 +155,160 @@ pub fn try_play_pause_toggle() -> bool {
         } else if pausing {
             MPMusicPlayerController::systemMusicPlayer().play();
         }
-        true
+        CommandResult::Ok
     }
 }
 
 #[cfg(target_os = "macos")]
-pub fn try_change_shuffle() -> bool {
+pub fn try_change_shuffle(shuffle: bool) -> CommandResult {
     unsafe {
-        MPMusicPlayerController::systemMusicPlayer().setShuffleMode(next_shuffle(MPMusicPlayerController::systemMusicPlayer().shuffleMode()));
-        true
+        let mode = MPMusicPlayerController::systemMusicPlayer().shuffleMode();
+        let shuffling = mode == MPMusicShuffleMode::Songs || mode == MPMusicShuffleMode::Albums;
+        // Already shuffling keeps its mode, songs or albums.
+        if shuffling != shuffle {
+            MPMusicPlayerController::systemMusicPlayer().setShuffleMode(if shuffle { MPMusicShuffleMode::Songs } else { MPMusicShuffleMode::Off });
+        }
+        CommandResult::Ok
     }
 }
 
 #[cfg(target_os = "macos")]
-pub fn try_change_repeat() -> bool {
+pub fn try_change_repeat() -> CommandResult {
     unsafe {
         MPMusicPlayerController::systemMusicPlayer().setRepeatMode(next_repeat(MPMusicPlayerController::systemMusicPlayer().repeatMode()));
-        true
+        CommandResult::Ok
     }
 }
 
 #[cfg(target_os = "macos")]
-pub fn try_change_playback_rate(f: f64) -> bool {
+pub fn try_change_playback_rate(f: f64) -> CommandResult {
     unsafe {
         MPMusicPlayerController::systemMusicPlayer().setCurrentPlaybackRate(c_float::from(f));
-        true
+        CommandResult::Ok
     }
 }
 
 #[cfg(target_os = "macos")]
-pub fn try_change_playback_position(i: i64) -> bool {
+pub fn try_change_playback_position(i: i64) -> CommandResult {
     unsafe {
         MPMusicPlayerController::systemMusicPlayer().setCurrentPlaybackTime(i as f64);
-        true
+        CommandResult::Ok
     }
 }
 
 #[cfg(target_os = "macos")]
-fn next_shuffle(mode: MPMusicShuffleMode) -> MPMusicShuffleMode {
-    if mode == MPMusicShuffleMode::Default {
-        mode
-    } else  if mode == MPMusicShuffleMode::Off {
-        return MPMusicShuffleMode::Songs;
-    } else if mode == MPMusicShuffleMode::Songs {
-        return MPMusicShuffleMode::Albums;
-    } else if mode == MPMusicShuffleMode::Albums {
-        return MPMusicShuffleMode::Off;
-    } else {
-        mode
-    }
+pub fn try_set_volume(_volume: f64) -> CommandResult {
+    CommandResult::NotSupported //because macos hasn't this method.
+}
+
+#[cfg(target_os = "macos")]
+pub fn try_open_uri(_uri: &str) -> CommandResult {
+    CommandResult::NotSupported //because macos hasn't this method.
+}
+
+#[cfg(target_os = "macos")]
+pub fn get_supported_uri_schemes() -> Vec<String> {
+    Vec::new()
+}
+
+#[cfg(target_os = "macos")]
+pub fn get_supported_mime_types() -> Vec<String> {
+    Vec::new()
+}
+
+#[cfg(target_os = "macos")]
+pub fn try_raise() -> CommandResult {
+    CommandResult::NotSupported //because macos hasn't this method.
+}
+
+#[cfg(target_os = "macos")]
+pub fn try_quit() -> CommandResult {
+    CommandResult::NotSupported //because macos hasn't this method.
+}
+
+#[cfg(target_os = "macos")]
+pub fn try_set_fullscreen(_fullscreen: bool) -> CommandResult {
+    CommandResult::NotSupported //because macos hasn't this method.
+}
+
+#[cfg(target_os = "macos")]
+pub fn can_raise() -> bool {
+    false
+}
+
+#[cfg(target_os = "macos")]
+pub fn can_quit() -> bool {
+    false
+}
+
+#[cfg(target_os = "macos")]
+pub fn can_set_fullscreen() -> bool {
+    false
+}
+
+#[cfg(target_os = "macos")]
+pub fn has_track_list() -> bool {
+    false
+}
+
+#[cfg(target_os = "macos")]
+pub fn get_track_list() -> Vec<TrackInfo> {
+    Vec::new()
+}
+
+#[cfg(target_os = "macos")]
+pub fn try_go_to(_track_id: &str) -> CommandResult {
+    CommandResult::NotSupported //because macos hasn't this method.
+}
+
+#[cfg(target_os = "macos")]
+pub fn try_add_track(_uri: &str, _after_track_id: Option<&str>, _set_as_current: bool) -> CommandResult {
+    CommandResult::NotSupported //because macos hasn't this method.
+}
+
+#[cfg(target_os = "macos")]
+pub fn try_remove_track(_track_id: &str) -> CommandResult {
+    CommandResult::NotSupported //because macos hasn't this method.
+}
+
+#[cfg(target_os = "macos")]
+pub fn get_playlists(_index: u32, _max_count: u32, _order: &str, _reverse_order: bool) -> Vec<PlaylistInfo> {
+    Vec::new()
+}
+
+#[cfg(target_os = "macos")]
+pub fn get_playlist_orderings() -> Vec<String> {
+    Vec::new()
+}
+
+#[cfg(target_os = "macos")]
+pub fn get_playlist_count() -> u32 {
+    0
+}
+
+#[cfg(target_os = "macos")]
+pub fn get_active_playlist() -> Option<PlaylistInfo> {
+    None
+}
+
+#[cfg(target_os = "macos")]
+pub fn try_activate_playlist(_playlist_id: &str) -> CommandResult {
+    CommandResult::NotSupported //because macos hasn't this method.
+}
+
+#[cfg(target_os = "macos")]
+pub fn get_players() -> Vec<(String, String)> {
+    vec![("system".to_string(), "Music".to_string())] //because macos only exposes the system music player.
+}
+
+#[cfg(target_os = "macos")]
+pub fn select_player(_name: Option<&str>) {
+    //because macos only exposes the system music player.
+}
+
+#[cfg(target_os = "macos")]
+pub fn with_player<T>(_name: &str, f: impl FnOnce() -> T) -> T {
+    f() //because macos only exposes the system music player.
 }
 
 #[cfg(target_os = "macos")]
@@ -1,17 +1,22 @@
 #[cfg(target_os = "windows")]
 use {
-    crate::{MediaInfo, PlaybackState},
+    crate::{position, CommandResult, MediaInfo, PlaybackState, PlaylistInfo, TrackInfo},
     std::{
+        cell::RefCell,
         env,
         fs::File,
-        io::Write
+        io::Write,
+        sync::Mutex
     },
     windows::{
         core::HSTRING,
+        Foundation::IAsyncOperation,
         Media::{
             Control::{
+                GlobalSystemMediaTransportControlsSession,
                 GlobalSystemMediaTransportControlsSessionManager,
                 GlobalSystemMediaTransportControlsSessionMediaProperties,
+                GlobalSystemMediaTransportControlsSessionPlaybackControls,
                 GlobalSystemMediaTransportControlsSessionPlaybackStatus
             },
             MediaPlaybackAutoRepeatMode
@@ -28,12 +33,22 @@ use {
     }
 };
 
+/// App id of the session to use instead of the current one, set by `select_player`.
+#[cfg(target_os = "windows")]
+static SELECTED: Mutex<Option<String>> = Mutex::new(None);
+
+#[cfg(target_os = "windows")]
+thread_local! {
+    /// App id of the session for this thread's calls only, set by `with_player`. Wins over `SELECTED`.
+    static THREAD_PLAYER: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
 #[cfg(target_os = "windows")]
 pub fn get_media_info() -> MediaInfo {
     if unavailable() {
         MediaInfo::empty()
     } else {
-        let session = GlobalSystemMediaTransportControlsSessionManager::RequestAsync().unwrap().get().unwrap().GetCurrentSession().unwrap();
+        let session = current_session().unwrap();
 
         let properties = session.TryGetMediaPropertiesAsync().unwrap().get().unwrap();
 
@@ -51,12 +66,12 @@ pub fn get_playback_state() -> PlaybackState {
     if unavailable() {
         PlaybackState::empty()
     } else {
-        let session = GlobalSystemMediaTransportControlsSessionManager::RequestAsync().unwrap().get().unwrap().GetCurrentSession().unwrap();
+        let session = current_session().unwrap();
 
         let timeline = &session.GetTimelineProperties().unwrap();
         let state = &session.GetPlaybackInfo().unwrap();
 
-        PlaybackState::new(
+        let playback_state = PlaybackState::new(
             GlobalSystemMediaTransportControlsSessionPlaybackStatus::Playing == state.PlaybackStatus().unwrap(),
             GlobalSystemMediaTransportControlsSessionPlaybackStatus::Paused == state.PlaybackStatus().unwrap(),
             GlobalSystemMediaTransportControlsSessionPlaybackStatus::Stopped == state.PlaybackStatus().unwrap(),
@@ -80,255 +95,274 @@ pub fn get_playback_state() -> PlaybackState {
             state.Controls().unwrap().IsRepeatEnabled().unwrap(),
             state.Controls().unwrap().IsPlaybackRateEnabled().unwrap(),
             state.Controls().unwrap().IsPlaybackPositionEnabled().unwrap(),
-        )
+        ).with_rate_and_volume(
+            state.PlaybackRate().and_then(|r| r.Value()).unwrap_or(1.0),
+            -1.0, //because windows hasn't this method.
+        );
+
+        // Keeps the interpolated position that progress ticks, triggers and seek detection read,
+        // unless the state is of a session picked for this thread's calls only.
+        if THREAD_PLAYER.with(|p| p.borrow().is_none()) {
+            position::update(playback_state.current_time, playback_state.playback_rate, playback_state.is_playing);
+        }
+        playback_state
     }
 }
 
 #[cfg(target_os = "windows")]
-pub fn try_play() -> bool {
-    if unavailable() {
-        false
-    } else {
-        match GlobalSystemMediaTransportControlsSessionManager::RequestAsync().unwrap().get().unwrap().GetCurrentSession().unwrap().TryPlayAsync() {
-            Ok(session) => {
-                session.get().unwrap()
-            },
-            Err(_) => {
-                false
-            }
-        }
-    }
+pub fn try_play() -> CommandResult {
+    command(|c| c.IsPlayEnabled(), |s| s.TryPlayAsync())
 }
 
 #[cfg(target_os = "windows")]
-pub fn try_pause() -> bool {
-    if unavailable() {
-        false
-    } else {
-        match GlobalSystemMediaTransportControlsSessionManager::RequestAsync().unwrap().get().unwrap().GetCurrentSession().unwrap().TryPauseAsync() {
-            Ok(session) => {
-                session.get().unwrap()
-            },
-            Err(_) => {
-                false
-            }
-        }
-    }
+pub fn try_pause() -> CommandResult {
+    command(|c| c.IsPauseEnabled(), |s| s.TryPauseAsync())
 }
 
 #[cfg(target_os = "windows")]
-pub fn try_stop() -> bool {
-    if unavailable() {
-        false
-    } else {
-        match GlobalSystemMediaTransportControlsSessionManager::RequestAsync().unwrap().get().unwrap().GetCurrentSession().unwrap().TryStopAsync() {
-            Ok(session) => {
-                session.get().unwrap()
-            },
-            Err(_) => {
-                false
-            }
-        }
-    }
+pub fn try_stop() -> CommandResult {
+    command(|c| c.IsStopEnabled(), |s| s.TryStopAsync())
 }
 
 #[cfg(target_os = "windows")]
-pub fn try_record() -> bool {
-    if unavailable() {
-        false
-    } else {
-        match GlobalSystemMediaTransportControlsSessionManager::RequestAsync().unwrap().get().unwrap().GetCurrentSession().unwrap().TryRecordAsync() {
-            Ok(session) => {
-                session.get().unwrap()
-            },
-            Err(_) => {
-                false
-            }
-        }
-    }
+pub fn try_record() -> CommandResult {
+    command(|c| c.IsRecordEnabled(), |s| s.TryRecordAsync())
 }
 
 #[cfg(target_os = "windows")]
-pub fn try_fast_forward() -> bool {
-    if unavailable() {
-        false
-    } else {
-        match GlobalSystemMediaTransportControlsSessionManager::RequestAsync().unwrap().get().unwrap().GetCurrentSession().unwrap().TryFastForwardAsync() {
-            Ok(session) => {
-                session.get().unwrap()
-            },
-            Err(_) => {
-                false
-            }
-        }
-    }
+pub fn try_fast_forward() -> CommandResult {
+    command(|c| c.IsFastForwardEnabled(), |s| s.TryFastForwardAsync())
 }
 
 #[cfg(target_os = "windows")]
-pub fn try_rewind() -> bool {
-    if unavailable() {
-        false
-    } else {
-        match GlobalSystemMediaTransportControlsSessionManager::RequestAsync().unwrap().get().unwrap().GetCurrentSession().unwrap().TryRewindAsync() {
-            Ok(session) => {
-                session.get().unwrap()
-            },
-            Err(_) => {
-                false
-            }
-        }
-    }
+pub fn try_rewind() -> CommandResult {
+    command(|c| c.IsRewindEnabled(), |s| s.TryRewindAsync())
 }
 
 #[cfg(target_os = "windows")]
-pub fn try_next() -> bool {
-    if unavailable() {
-        false
-    } else {
-        match GlobalSystemMediaTransportControlsSessionManager::RequestAsync().unwrap().get().unwrap().GetCurrentSession().unwrap().TrySkipNextAsync() {
-            Ok(session) => {
-                session.get().unwrap()
-            },
-            Err(_) => {
-                false
-            }
-        }
-    }
+pub fn try_next() -> CommandResult {
+    command(|c| c.IsNextEnabled(), |s| s.TrySkipNextAsync())
 }
 
 #[cfg(target_os = "windows")]
-pub fn try_previous() -> bool {
-    if unavailable() {
-        false
-    } else {
-        match GlobalSystemMediaTransportControlsSessionManager::RequestAsync().unwrap().get().unwrap().GetCurrentSession().unwrap().TrySkipPreviousAsync() {
-            Ok(session) => {
-                session.get().unwrap()
-            },
-            Err(_) => {
-                false
-            }
-        }
-    }
+pub fn try_previous() -> CommandResult {
+    command(|c| c.IsPreviousEnabled(), |s| s.TrySkipPreviousAsync())
 }
 
 #[cfg(target_os = "windows")]
-pub fn try_change_channel_up() -> bool {
-    if unavailable() {
-        false
-    } else {
-        match GlobalSystemMediaTransportControlsSessionManager::RequestAsync().unwrap().get().unwrap().GetCurrentSession().unwrap().TryChangeChannelUpAsync() {
-            Ok(session) => {
-                session.get().unwrap()
-            },
-            Err(_) => {
-                false
-            }
-        }
-    }
+pub fn try_change_channel_up() -> CommandResult {
+    command(|c| c.IsChannelUpEnabled(), |s| s.TryChangeChannelUpAsync())
 }
 
 #[cfg(target_os = "windows")]
-pub fn try_change_channel_down() -> bool {
-    if unavailable() {
-        false
-    } else {
-        match GlobalSystemMediaTransportControlsSessionManager::RequestAsync().unwrap().get().unwrap().GetCurrentSession().unwrap().TryChangeChannelDownAsync() {
-            Ok(session) => {
-                session.get().unwrap()
-            },
-            Err(_) => {
-                false
-            }
-        }
-    }
+pub fn try_change_channel_down() -> CommandResult {
+    command(|c| c.IsChannelDownEnabled(), |s| s.TryChangeChannelDownAsync())
 }
 
 #[cfg(target_os = "windows")]
-pub fn try_play_pause_toggle() -> bool {
-    if unavailable() {
-        false
-    } else {
-        match GlobalSystemMediaTransportControlsSessionManager::RequestAsync().unwrap().get().unwrap().GetCurrentSession().unwrap().TryTogglePlayPauseAsync() {
-            Ok(session) => {
-                session.get().unwrap()
-            },
-            Err(_) => {
-                false
-            }
-        }
-    }
+pub fn try_play_pause_toggle() -> CommandResult {
+    command(|c| c.IsPlayPauseToggleEnabled(), |s| s.TryTogglePlayPauseAsync())
 }
 
 #[cfg(target_os = "windows")]
-pub fn try_change_shuffle(shuffle: bool) -> bool {
-    if unavailable() {
-        false
-    } else {
-        match GlobalSystemMediaTransportControlsSessionManager::RequestAsync().unwrap().get().unwrap().GetCurrentSession().unwrap().TryChangeShuffleActiveAsync(shuffle) {
-            Ok(session) => {
-                session.get().unwrap()
-            },
-            Err(_) => {
-                false
-            }
-        }
-    }
+pub fn try_change_shuffle(shuffle: bool) -> CommandResult {
+    command(|c| c.IsShuffleEnabled(), |s| s.TryChangeShuffleActiveAsync(shuffle))
 }
 
 #[cfg(target_os = "windows")]
-pub fn try_change_repeat() -> bool {
-    if unavailable() {
-        false
-    } else {
-        match GlobalSystemMediaTransportControlsSessionManager::RequestAsync().unwrap().get().unwrap().GetCurrentSession().unwrap().TryChangeAutoRepeatModeAsync(
-            next(GlobalSystemMediaTransportControlsSessionManager::RequestAsync().unwrap().get().unwrap().GetCurrentSession().unwrap().GetPlaybackInfo().unwrap().AutoRepeatMode().unwrap().Value().unwrap())
-        ) {
-            Ok(session) => {
-                session.get().unwrap()
-            },
-            Err(_) => {
-                false
-            }
-        }
-    }
+pub fn try_change_repeat() -> CommandResult {
+    command(|c| c.IsRepeatEnabled(), |s| s.TryChangeAutoRepeatModeAsync(next(s.GetPlaybackInfo()?.AutoRepeatMode()?.Value()?)))
 }
 
 #[cfg(target_os = "windows")]
-pub fn try_change_playback_rate(i: f64) -> bool {
-    if unavailable() {
-        false
-    } else {
-        match GlobalSystemMediaTransportControlsSessionManager::RequestAsync().unwrap().get().unwrap().GetCurrentSession().unwrap().TryChangePlaybackRateAsync(i) {
-            Ok(session) => {
-                session.get().unwrap()
-            },
-            Err(_) => {
-                false
-            }
-        }
-    }
+pub fn try_change_playback_rate(i: f64) -> CommandResult {
+    command(|c| c.IsPlaybackRateEnabled(), |s| s.TryChangePlaybackRateAsync(i))
 }
 
 #[cfg(target_os = "windows")]
-pub fn try_change_playback_position(i: i64) -> bool {
-    if unavailable() {
-        false
-    } else {
-        match GlobalSystemMediaTransportControlsSessionManager::RequestAsync().unwrap().get().unwrap().GetCurrentSession().unwrap().TryChangePlaybackPositionAsync(i) {
-            Ok(session) => {
-                session.get().unwrap()
-            },
-            Err(_) => {
-                false
-            }
-        }
-    }
+pub fn try_change_playback_position(i: i64) -> CommandResult {
+    command(|c| c.IsPlaybackPositionEnabled(), |s| s.TryChangePlaybackPositionAsync(i))
+}
+
+#[cfg(target_os = "windows")]
+pub fn try_set_volume(_volume: f64) -> CommandResult {
+    CommandResult::NotSupported //because windows hasn't this method.
+}
+
+#[cfg(target_os = "windows")]
+pub fn try_open_uri(_uri: &str) -> CommandResult {
+    CommandResult::NotSupported //because windows hasn't this method.
+}
+
+#[cfg(target_os = "windows")]
+pub fn get_supported_uri_schemes() -> Vec<String> {
+    Vec::new()
+}
+
+#[cfg(target_os = "windows")]
+pub fn get_supported_mime_types() -> Vec<String> {
+    Vec::new()
+}
+
+#[cfg(target_os = "windows")]
+pub fn try_raise() -> CommandResult {
+    CommandResult::NotSupported //because windows hasn't this method.
+}
+
+#[cfg(target_os = "windows")]
+pub fn try_quit() -> CommandResult {
+    CommandResult::NotSupported //because windows hasn't this method.
+}
+
+#[cfg(target_os = "windows")]
+pub fn try_set_fullscreen(_fullscreen: bool) -> CommandResult {
+    CommandResult::NotSupported //because windows hasn't this method.
+}
+
+#[cfg(target_os = "windows")]
+pub fn can_raise() -> bool {
+    false
+}
+
+#[cfg(target_os = "windows")]
+pub fn can_quit() -> bool {
+    false
+}
+
+#[cfg(target_os = "windows")]
+pub fn can_set_fullscreen() -> bool {
+    false
+}
+
+#[cfg(target_os = "windows")]
+pub fn has_track_list() -> bool {
+    false
+}
+
+#[cfg(target_os = "windows")]
+pub fn get_track_list() -> Vec<TrackInfo> {
+    Vec::new()
+}
+
+#[cfg(target_os = "windows")]
+pub fn try_go_to(_track_id: &str) -> CommandResult {
+    CommandResult::NotSupported //because windows hasn't this method.
+}
+
+#[cfg(target_os = "windows")]
+pub fn try_add_track(_uri: &str, _after_track_id: Option<&str>, _set_as_current: bool) -> CommandResult {
+    CommandResult::NotSupported //because windows hasn't this method.
+}
+
+#[cfg(target_os = "windows")]
+pub fn try_remove_track(_track_id: &str) -> CommandResult {
+    CommandResult::NotSupported //because windows hasn't this method.
+}
+
+#[cfg(target_os = "windows")]
+pub fn get_playlists(_index: u32, _max_count: u32, _order: &str, _reverse_order: bool) -> Vec<PlaylistInfo> {
+    Vec::new()
+}
+
+#[cfg(target_os = "windows")]
+pub fn get_playlist_orderings() -> Vec<String> {
+    Vec::new()
+}
+
+#[cfg(target_os = "windows")]
+pub fn get_playlist_count() -> u32 {
+    0
+}
+
+#[cfg(target_os = "windows")]
+pub fn get_active_playlist() -> Option<PlaylistInfo> {
+    None
+}
+
+#[cfg(target_os = "windows")]
+pub fn try_activate_playlist(_playlist_id: &str) -> CommandResult {
+    CommandResult::NotSupported //because windows hasn't this method.
+}
+
+/// Windows only knows sessions by their app id, which serves as both name and identity.
+#[cfg(target_os = "windows")]
+pub fn get_players() -> Vec<(String, String)> {
+    GlobalSystemMediaTransportControlsSessionManager::RequestAsync()
+        .and_then(|manager| manager.get())
+        .and_then(|manager| manager.GetSessions())
+        .map(|sessions| {
+            sessions
+                .into_iter()
+                .filter_map(|session| session.SourceAppUserModelId().ok())
+                .map(|id| (id.to_string(), id.to_string()))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Makes every call go to the session whose app id matches `name`, ignoring case, instead of the
+/// current one, or back to the current one for `None`.
+#[cfg(target_os = "windows")]
+pub fn select_player(name: Option<&str>) {
+    *SELECTED.lock().unwrap() = name.map(str::to_string);
+}
+
+/// Runs `f` with this thread's calls going to the session whose app id matches `name`.
+#[cfg(target_os = "windows")]
+pub fn with_player<T>(name: &str, f: impl FnOnce() -> T) -> T {
+    let previous = THREAD_PLAYER.with(|p| p.replace(Some(name.to_string())));
+    let result = f();
+    THREAD_PLAYER.with(|p| *p.borrow_mut() = previous);
+    result
 }
 
 #[cfg(target_os = "windows")]
 pub fn unavailable() -> bool {
-    GlobalSystemMediaTransportControlsSessionManager::RequestAsync().unwrap().get().unwrap().GetSessions().iter().next().is_none()
+    current_session().is_err()
+}
+
+/// The thread's or selected session, or the current one when none is.
+#[cfg(target_os = "windows")]
+fn current_session() -> windows::core::Result<GlobalSystemMediaTransportControlsSession> {
+    let manager = GlobalSystemMediaTransportControlsSessionManager::RequestAsync()?.get()?;
+    let name = THREAD_PLAYER.with(|p| p.borrow().clone()).or_else(|| SELECTED.lock().unwrap().clone());
+    let Some(name) = name else {
+        return manager.GetCurrentSession();
+    };
+
+    manager.GetSessions()?
+        .into_iter()
+        .find(|session| session.SourceAppUserModelId().is_ok_and(|id| id.to_string().eq_ignore_ascii_case(&name)))
+        .ok_or_else(windows::core::Error::empty)
+}
+
+/// Runs `action` on the current session if `enabled` allows it, telling apart why it didn't.
+#[cfg(target_os = "windows")]
+fn command<C, A>(enabled: C, action: A) -> CommandResult
+where
+    C: FnOnce(&GlobalSystemMediaTransportControlsSessionPlaybackControls) -> windows::core::Result<bool>,
+    A: FnOnce(&GlobalSystemMediaTransportControlsSession) -> windows::core::Result<IAsyncOperation<bool>>,
+{
+    if unavailable() {
+        return CommandResult::NoPlayer;
+    }
+
+    let session = match current_session() {
+        Ok(session) => session,
+        Err(_) => return CommandResult::NoPlayer,
+    };
+
+    match session.GetPlaybackInfo().and_then(|i| i.Controls()).and_then(|c| enabled(&c)) {
+        Ok(true) => {}
+        Ok(false) => return CommandResult::CapabilityDisabled,
+        Err(e) => return CommandResult::Failed(e.to_string()),
+    }
+
+    match action(&session).and_then(|o| o.get()) {
+        Ok(true) => CommandResult::Ok,
+        Ok(false) => CommandResult::Failed("the session rejected the request".to_string()),
+        Err(e) => CommandResult::Failed(e.to_string()),
+    }
 }
 
 //noinspection Annotator
@@ -0,0 +1,142 @@
+use {
+    crate::{
+        event::{self, Event},
+        position,
+        Snapshot
+    },
+    std::{
+        collections::{HashMap, HashSet},
+        sync::{mpsc::Sender, Condvar, Mutex},
+        time::Duration
+    }
+};
+
+/// Players that change this many times without a signal are switched to polling.
+const SILENT_CHANGES: u32 = 2;
+
+/// Players still trusted to signal are only probed every this many intervals.
+const PROBE_EVERY: u32 = 5;
+
+/// How far the polled position may stray from the interpolated one before it counts as a seek.
+const SEEK_TOLERANCE: Duration = Duration::from_millis(1500);
+
+static INTERVAL: Mutex<Duration> = Mutex::new(Duration::from_millis(1000));
+
+/// Wakes the poller out of its wait for a new interval or shutdown.
+static WAKE: Condvar = Condvar::new();
+
+/// Players that sent `PropertiesChanged` since they were last polled.
+static SIGNALED: Mutex<Option<HashSet<String>>> = Mutex::new(None);
+
+/// Players caught changing silently, which are polled every interval from then on.
+static POLLED: Mutex<Option<HashSet<String>>> = Mutex::new(None);
+
+pub(crate) fn set_interval(interval: Duration) {
+    *INTERVAL.lock().unwrap() = interval.max(Duration::from_millis(50));
+    WAKE.notify_all();
+}
+
+/// Wakes the poller so it notices `event::stop` instead of waiting out the interval.
+pub(crate) fn shutdown() {
+    // Taken so the wakeup can't slip in between the poller's check and its wait.
+    let _interval = INTERVAL.lock().unwrap();
+    WAKE.notify_all();
+}
+
+pub(crate) fn signaled(player: &str) {
+    SIGNALED.lock().unwrap().get_or_insert_with(HashSet::new).insert(player.to_string());
+}
+
+/// Drops what is known about `player`, which left the bus.
+pub(crate) fn forget(player: &str) {
+    for set in [&SIGNALED, &POLLED] {
+        if let Some(players) = set.lock().unwrap().as_mut() {
+            players.remove(player);
+        }
+    }
+}
+
+pub(crate) fn polled_players() -> Vec<String> {
+    POLLED.lock().unwrap().iter().flatten().cloned().collect()
+}
+
+/// Polls the active player on a background thread, feeding changes it finds into `sender`.
+pub(crate) fn spawn(sender: Sender<Event>) {
+    let epoch = event::epoch();
+    event::spawn(move || {
+        let mut last: Option<(String, Snapshot)> = None;
+        let mut silent_changes: HashMap<String, u32> = HashMap::new();
+        let mut tick: u32 = 0;
+
+        loop {
+            // Setting a new interval ends the wait early, so a long one doesn't hold up the change.
+            let interval = INTERVAL.lock().unwrap();
+            let duration = *interval;
+            drop(WAKE.wait_timeout_while(interval, duration, |interval| *interval == duration && event::running(epoch)).unwrap());
+            if !event::running(epoch) {
+                return;
+            }
+            tick = tick.wrapping_add(1);
+
+            let Some(player) = active_player() else {
+                last = None;
+                continue;
+            };
+
+            let polled = !signals_supported() || POLLED.lock().unwrap().as_ref().is_some_and(|p| p.contains(&player));
+            if !polled && !tick.is_multiple_of(PROBE_EVERY) {
+                continue;
+            }
+
+            // Read before the snapshot, which moves the cache on to the polled position.
+            let expected = position::current();
+            let snapshot = Snapshot::take();
+            let signaled = SIGNALED.lock().unwrap().as_mut().is_some_and(|s| s.remove(&player));
+
+            if let Some((last_player, last_snapshot)) = &last {
+                if *last_player == player {
+                    let changed = !snapshot.same_state(last_snapshot);
+
+                    if changed && !signaled && !polled {
+                        let count = silent_changes.entry(player.clone()).or_insert(0);
+                        *count += 1;
+                        if *count >= SILENT_CHANGES {
+                            POLLED.lock().unwrap().get_or_insert_with(HashSet::new).insert(player.clone());
+                        }
+                    }
+
+                    if polled {
+                        if changed && sender.send(Event::PropertiesChanged).is_err() {
+                            return;
+                        }
+
+                        let current = snapshot.playback_state.current_time;
+                        let seeked = expected.is_some_and(|e| (current - e).abs() > position::units(SEEK_TOLERANCE));
+                        if seeked && sender.send(Event::Seeked(current)).is_err() {
+                            return;
+                        }
+                    }
+                }
+            }
+
+            last = Some((player, snapshot));
+        }
+    });
+}
+
+/// Without a signal path every player is polled.
+fn signals_supported() -> bool {
+    cfg!(target_os = "linux")
+}
+
+fn active_player() -> Option<String> {
+    #[cfg(target_os = "linux")]
+    {
+        crate::platform::linux::active_player_name()
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        crate::media::player_available().then(|| "system".to_string())
+    }
+}
@@ -0,0 +1,78 @@
+use {
+    crate::media,
+    std::{
+        sync::Mutex,
+        time::{Duration, Instant}
+    }
+};
+
+/// Last known playback position of the active player, in the platform's `currentTime` units,
+/// interpolated forward while it is playing.
+struct Position {
+    time: i64,
+    rate: f64,
+    playing: bool,
+    at: Instant,
+}
+
+static POSITION: Mutex<Option<Position>> = Mutex::new(None);
+
+pub(crate) fn update(time: i64, rate: f64, playing: bool) {
+    *POSITION.lock().unwrap() = Some(Position {
+        time,
+        rate,
+        playing,
+        at: Instant::now(),
+    });
+}
+
+/// Restarts interpolation from `time`, keeping the last known rate and playing state. Without
+/// one, the position holds still until the next snapshot tells whether the player is playing.
+pub(crate) fn seeked(time: i64) {
+    let mut position = POSITION.lock().unwrap();
+    let (rate, playing) = position.as_ref().map_or((1.0, false), |p| (p.rate, p.playing));
+    *position = Some(Position {
+        time,
+        rate,
+        playing,
+        at: Instant::now(),
+    });
+}
+
+/// Forgets the position, for when the player it belongs to is gone.
+pub(crate) fn clear() {
+    *POSITION.lock().unwrap() = None;
+}
+
+pub(crate) fn current() -> Option<i64> {
+    POSITION.lock().unwrap().as_ref().map(Position::now)
+}
+
+pub(crate) fn playing() -> bool {
+    POSITION.lock().unwrap().as_ref().is_some_and(|p| p.playing)
+}
+
+/// How long until the interpolated position reaches `time`, or `None` while it isn't moving towards it.
+pub(crate) fn until(time: i64) -> Option<Duration> {
+    let position = POSITION.lock().unwrap();
+    let p = position.as_ref().filter(|p| p.playing && p.rate > 0.0)?;
+
+    let seconds = (time - p.now()).max(0) as f64 / media::time_units_per_second() as f64 / p.rate;
+    Duration::try_from_secs_f64(seconds).ok()
+}
+
+/// `duration` in `currentTime` units, rounded up so a short tolerance doesn't vanish on platforms
+/// that count in whole seconds.
+pub(crate) fn units(duration: Duration) -> i64 {
+    (duration.as_secs_f64() * media::time_units_per_second() as f64).ceil() as i64
+}
+
+impl Position {
+    fn now(&self) -> i64 {
+        if self.playing {
+            self.time + (self.at.elapsed().as_secs_f64() * self.rate * media::time_units_per_second() as f64) as i64
+        } else {
+            self.time
+        }
+    }
+}
@@ -0,0 +1,304 @@
+//! The `mediainfo` Python extension module, built as a wheel with `maturin build`.
+
+use {
+    crate::{
+        event::{self, Event},
+        media,
+        CommandResult,
+        MediaInfo,
+        Snapshot
+    },
+    pyo3::{
+        exceptions::PyRuntimeError,
+        prelude::*,
+        types::PyDict
+    },
+    std::{
+        sync::{
+            mpsc::{self, Receiver, RecvTimeoutError},
+            Mutex
+        },
+        time::Duration
+    }
+};
+
+/// How long `Events` waits for an event before checking for a pending `KeyboardInterrupt`.
+const SIGNAL_CHECK_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Iterator over `(name, data)` tuples, the same pair Java listeners get in `onEvent`.
+#[pyclass]
+struct Events {
+    receiver: Mutex<Receiver<Event>>,
+}
+
+#[pymethods]
+impl Events {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(&self, py: Python<'_>) -> PyResult<Option<(&'static str, String)>> {
+        loop {
+            // Let other Python threads run while this one waits for the player, waking now and
+            // then so Ctrl-C gets through.
+            match py.allow_threads(|| self.receiver.lock().unwrap().recv_timeout(SIGNAL_CHECK_INTERVAL)) {
+                Ok(event) => return Ok(Some((event.name(), event.to_string()))),
+                Err(RecvTimeoutError::Timeout) => py.check_signals()?,
+                Err(RecvTimeoutError::Disconnected) => return Ok(None),
+            }
+        }
+    }
+}
+
+/// The active player's media info and playback state as a dict.
+#[pyfunction]
+fn snapshot(py: Python<'_>) -> PyResult<Bound<'_, PyDict>> {
+    to_dict(py, &py.allow_threads(media::snapshot))
+}
+
+/// `(name, identity)` of every player.
+#[pyfunction]
+fn players(py: Python<'_>) -> Vec<(String, String)> {
+    py.allow_threads(media::players)
+}
+
+/// The player's track list as dicts of `track_id` and the media info fields.
+#[pyfunction]
+fn track_list(py: Python<'_>) -> PyResult<Vec<Bound<'_, PyDict>>> {
+    py.allow_threads(media::track_list)
+        .iter()
+        .map(|track| {
+            let dict = media_info_dict(py, track.media_info())?;
+            dict.set_item("track_id", track.track_id())?;
+            Ok(dict)
+        })
+        .collect()
+}
+
+/// A page of the player's playlists as dicts of `playlist_id`, `name` and `icon`, like
+/// `NativeMedia.getPlaylists`.
+#[pyfunction]
+#[pyo3(signature = (index = 0, max_count = 100, order = "Alphabetical", reverse_order = false))]
+fn playlists<'py>(py: Python<'py>, index: u32, max_count: u32, order: &str, reverse_order: bool) -> PyResult<Vec<Bound<'py, PyDict>>> {
+    py.allow_threads(|| media::playlists(index, max_count, order, reverse_order))
+        .iter()
+        .map(|playlist| {
+            let dict = PyDict::new(py);
+            dict.set_item("playlist_id", playlist.playlist_id())?;
+            dict.set_item("name", playlist.name())?;
+            dict.set_item("icon", playlist.icon())?;
+            Ok(dict)
+        })
+        .collect()
+}
+
+/// Subscribes to player events; a burst of property changes is coalesced into one snapshot, sent
+/// `window_ms` after its first change.
+#[pyfunction]
+#[pyo3(signature = (window_ms = 0))]
+fn events(window_ms: u64) -> Events {
+    let (sender, receiver) = mpsc::channel();
+    event::subscribe_coalesced(Duration::from_millis(window_ms), move |event| sender.send(event).is_ok());
+    Events {
+        receiver: Mutex::new(receiver),
+    }
+}
+
+#[pyfunction]
+fn play(py: Python<'_>) -> PyResult<&'static str> {
+    command(py.allow_threads(media::play))
+}
+
+#[pyfunction]
+fn pause(py: Python<'_>) -> PyResult<&'static str> {
+    command(py.allow_threads(media::pause))
+}
+
+#[pyfunction]
+fn stop(py: Python<'_>) -> PyResult<&'static str> {
+    command(py.allow_threads(media::stop))
+}
+
+#[pyfunction]
+fn record(py: Python<'_>) -> PyResult<&'static str> {
+    command(py.allow_threads(media::record))
+}
+
+#[pyfunction]
+fn fast_forward(py: Python<'_>) -> PyResult<&'static str> {
+    command(py.allow_threads(media::fast_forward))
+}
+
+#[pyfunction]
+fn rewind(py: Python<'_>) -> PyResult<&'static str> {
+    command(py.allow_threads(media::rewind))
+}
+
+#[pyfunction]
+fn toggle_play_pause(py: Python<'_>) -> PyResult<&'static str> {
+    command(py.allow_threads(media::toggle_play_pause))
+}
+
+#[pyfunction]
+fn next(py: Python<'_>) -> PyResult<&'static str> {
+    command(py.allow_threads(media::next))
+}
+
+#[pyfunction]
+fn previous(py: Python<'_>) -> PyResult<&'static str> {
+    command(py.allow_threads(media::previous))
+}
+
+#[pyfunction]
+fn channel_up(py: Python<'_>) -> PyResult<&'static str> {
+    command(py.allow_threads(media::channel_up))
+}
+
+#[pyfunction]
+fn channel_down(py: Python<'_>) -> PyResult<&'static str> {
+    command(py.allow_threads(media::channel_down))
+}
+
+#[pyfunction]
+fn shuffle(py: Python<'_>, shuffle: bool) -> PyResult<&'static str> {
+    command(py.allow_threads(|| media::shuffle(shuffle)))
+}
+
+#[pyfunction]
+fn repeat(py: Python<'_>) -> PyResult<&'static str> {
+    command(py.allow_threads(media::repeat))
+}
+
+#[pyfunction]
+fn set_playback_rate(py: Python<'_>, rate: f64) -> PyResult<&'static str> {
+    command(py.allow_threads(|| media::set_playback_rate(rate)))
+}
+
+#[pyfunction]
+fn set_playback_position(py: Python<'_>, position: i64) -> PyResult<&'static str> {
+    command(py.allow_threads(|| media::set_playback_position(position)))
+}
+
+/// Moves the position by `seconds`, backwards when negative.
+#[pyfunction]
+fn seek(py: Python<'_>, seconds: f64) -> PyResult<&'static str> {
+    command(py.allow_threads(|| media::seek(seconds)))
+}
+
+/// `volume` is linear from 0.0 to 1.0.
+#[pyfunction]
+fn set_volume(py: Python<'_>, volume: f64) -> PyResult<&'static str> {
+    command(py.allow_threads(|| media::set_volume(volume)))
+}
+
+#[pyfunction]
+fn open_uri(py: Python<'_>, uri: &str) -> PyResult<&'static str> {
+    command(py.allow_threads(|| media::open_uri(uri)))
+}
+
+#[pyfunction]
+fn raise(py: Python<'_>) -> PyResult<&'static str> {
+    command(py.allow_threads(media::raise))
+}
+
+#[pyfunction]
+fn quit(py: Python<'_>) -> PyResult<&'static str> {
+    command(py.allow_threads(media::quit))
+}
+
+#[pyfunction]
+fn set_fullscreen(py: Python<'_>, fullscreen: bool) -> PyResult<&'static str> {
+    command(py.allow_threads(|| media::set_fullscreen(fullscreen)))
+}
+
+#[pyfunction]
+fn go_to(py: Python<'_>, track_id: &str) -> PyResult<&'static str> {
+    command(py.allow_threads(|| media::go_to(track_id)))
+}
+
+#[pyfunction]
+#[pyo3(signature = (uri, after_track_id = None, set_as_current = false))]
+fn add_track(py: Python<'_>, uri: &str, after_track_id: Option<&str>, set_as_current: bool) -> PyResult<&'static str> {
+    command(py.allow_threads(|| media::add_track(uri, after_track_id, set_as_current)))
+}
+
+#[pyfunction]
+fn remove_track(py: Python<'_>, track_id: &str) -> PyResult<&'static str> {
+    command(py.allow_threads(|| media::remove_track(track_id)))
+}
+
+#[pyfunction]
+fn activate_playlist(py: Python<'_>, playlist_id: &str) -> PyResult<&'static str> {
+    command(py.allow_threads(|| media::activate_playlist(playlist_id)))
+}
+
+#[pymodule]
+fn mediainfo(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<Events>()?;
+    m.add_function(wrap_pyfunction!(snapshot, m)?)?;
+    m.add_function(wrap_pyfunction!(players, m)?)?;
+    m.add_function(wrap_pyfunction!(track_list, m)?)?;
+    m.add_function(wrap_pyfunction!(playlists, m)?)?;
+    m.add_function(wrap_pyfunction!(events, m)?)?;
+    m.add_function(wrap_pyfunction!(play, m)?)?;
+    m.add_function(wrap_pyfunction!(pause, m)?)?;
+    m.add_function(wrap_pyfunction!(stop, m)?)?;
+    m.add_function(wrap_pyfunction!(record, m)?)?;
+    m.add_function(wrap_pyfunction!(fast_forward, m)?)?;
+    m.add_function(wrap_pyfunction!(rewind, m)?)?;
+    m.add_function(wrap_pyfunction!(toggle_play_pause, m)?)?;
+    m.add_function(wrap_pyfunction!(next, m)?)?;
+    m.add_function(wrap_pyfunction!(previous, m)?)?;
+    m.add_function(wrap_pyfunction!(channel_up, m)?)?;
+    m.add_function(wrap_pyfunction!(channel_down, m)?)?;
+    m.add_function(wrap_pyfunction!(shuffle, m)?)?;
+    m.add_function(wrap_pyfunction!(repeat, m)?)?;
+    m.add_function(wrap_pyfunction!(set_playback_rate, m)?)?;
+    m.add_function(wrap_pyfunction!(set_playback_position, m)?)?;
+    m.add_function(wrap_pyfunction!(seek, m)?)?;
+    m.add_function(wrap_pyfunction!(set_volume, m)?)?;
+    m.add_function(wrap_pyfunction!(open_uri, m)?)?;
+    m.add_function(wrap_pyfunction!(raise, m)?)?;
+    m.add_function(wrap_pyfunction!(quit, m)?)?;
+    m.add_function(wrap_pyfunction!(set_fullscreen, m)?)?;
+    m.add_function(wrap_pyfunction!(go_to, m)?)?;
+    m.add_function(wrap_pyfunction!(add_track, m)?)?;
+    m.add_function(wrap_pyfunction!(remove_track, m)?)?;
+    m.add_function(wrap_pyfunction!(activate_playlist, m)?)?;
+    Ok(())
+}
+
+/// Returns the outcome's name like the Java enum; only `Failed` raises, carrying the player's message.
+fn command(result: CommandResult) -> PyResult<&'static str> {
+    match result {
+        CommandResult::Ok => Ok("Ok"),
+        CommandResult::NotSupported => Ok("NotSupported"),
+        CommandResult::NoPlayer => Ok("NoPlayer"),
+        CommandResult::CapabilityDisabled => Ok("CapabilityDisabled"),
+        CommandResult::Failed(message) => Err(PyRuntimeError::new_err(message)),
+    }
+}
+
+fn to_dict<'py>(py: Python<'py>, snapshot: &Snapshot) -> PyResult<Bound<'py, PyDict>> {
+    let state = snapshot.playback_state();
+    let dict = media_info_dict(py, snapshot.media_info())?;
+
+    dict.set_item("status", state.status())?;
+    dict.set_item("shuffle", state.is_shuffling())?;
+    dict.set_item("repeat", state.repeat())?;
+    dict.set_item("position", state.current_time())?;
+    dict.set_item("length", state.max_time())?;
+    dict.set_item("rate", state.playback_rate())?;
+    dict.set_item("volume", state.volume())?;
+    dict.set_item("capabilities", state.capabilities())?;
+    Ok(dict)
+}
+
+fn media_info_dict<'py>(py: Python<'py>, media_info: &MediaInfo) -> PyResult<Bound<'py, PyDict>> {
+    let dict = PyDict::new(py);
+    dict.set_item("title", media_info.title())?;
+    dict.set_item("artist", media_info.artist())?;
+    dict.set_item("album", media_info.album())?;
+    dict.set_item("album_art", media_info.album_art())?;
+    Ok(dict)
+}
@@ -0,0 +1,314 @@
+//! JSON-RPC 2.0 on a Unix domain socket, one request or batch per line, for local scripts that
+//! shouldn't need a TCP port.
+//!
+//! The `NativeController` commands are methods of the same name, taking their argument as the
+//! first positional parameter or as `value`. `getMediaInfo`, `getPlaybackState`, `getSnapshot`,
+//! `getGeneration` and `getPlayers` read the player. After `subscribe`, optionally with
+//! `windowMs`, every event arrives on the same connection as an `event` notification until
+//! `unsubscribe`.
+
+use {
+    crate::{
+        control,
+        event,
+        generation,
+        media
+    },
+    anyhow::{bail, Result},
+    serde_json::{json, Value},
+    std::{
+        env,
+        fs,
+        io::{BufRead, BufReader, Write},
+        os::unix::{
+            fs::{DirBuilderExt, FileTypeExt, PermissionsExt},
+            net::{UnixListener, UnixStream}
+        },
+        path::{Path, PathBuf},
+        sync::{
+            atomic::{AtomicI64, AtomicU64, Ordering},
+            Arc,
+            Mutex
+        },
+        time::Duration
+    }
+};
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+/// Where to listen. Built with [`Server::new`] or [`Server::with_default_path`].
+#[derive(Clone, Debug)]
+pub struct Server {
+    path: PathBuf,
+}
+
+struct Running {
+    id: i64,
+    path: PathBuf,
+    /// Every open connection by number, so stopping can end the threads blocked reading them.
+    connections: Vec<(u64, UnixStream)>,
+}
+
+static RUNNING: Mutex<Vec<Running>> = Mutex::new(Vec::new());
+static NEXT_ID: AtomicI64 = AtomicI64::new(1);
+
+/// One client. Responses and event notifications share the stream, so writes take the lock.
+struct Connection {
+    stream: Mutex<UnixStream>,
+    /// Bumped by `subscribe` and `unsubscribe`, so only the latest subscription keeps sending.
+    subscription: AtomicU64,
+}
+
+impl Server {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// `$XDG_RUNTIME_DIR/mediainfo.sock`, or `None` when the variable isn't set.
+    pub fn with_default_path() -> Option<Self> {
+        let directory = env::var_os("XDG_RUNTIME_DIR").filter(|d| !d.is_empty())?;
+        Some(Self::new(Path::new(&directory).join("mediainfo.sock")))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Binds the socket, replacing one left behind by a process that is gone, and serves on a
+    /// background thread. Outside `$XDG_RUNTIME_DIR` the socket is private to the user from the
+    /// start, as anyone able to connect can control the player. Returns an id for [`stop`].
+    pub fn start(self) -> Result<i64> {
+        if let Ok(metadata) = fs::symlink_metadata(&self.path) {
+            if !metadata.file_type().is_socket() {
+                bail!("{} exists and isn't a socket", self.path.display());
+            }
+            if UnixStream::connect(&self.path).is_ok() {
+                bail!("{} is already being served", self.path.display());
+            }
+            fs::remove_file(&self.path)?;
+        }
+
+        let runtime_directory = env::var_os("XDG_RUNTIME_DIR").filter(|d| !d.is_empty());
+        let listener = if runtime_directory.is_some_and(|d| self.path.starts_with(d)) {
+            UnixListener::bind(&self.path)?
+        } else {
+            bind_private(&self.path)?
+        };
+        let id = NEXT_ID.fetch_add(1, Ordering::SeqCst);
+        RUNNING.lock().unwrap().push(Running {
+            id,
+            path: self.path.clone(),
+            connections: Vec::new(),
+        });
+
+        event::spawn(move || {
+            for (number, stream) in (0..).zip(listener.incoming()) {
+                let mut running = RUNNING.lock().unwrap();
+                // `stop` connects once to get here after taking the server off the list.
+                let Some(entry) = running.iter_mut().find(|r| r.id == id) else {
+                    return;
+                };
+                let Ok(stream) = stream else {
+                    continue;
+                };
+                if let Ok(clone) = stream.try_clone() {
+                    entry.connections.push((number, clone));
+                    event::spawn(move || {
+                        serve(stream);
+                        if let Some(entry) = RUNNING.lock().unwrap().iter_mut().find(|r| r.id == id) {
+                            entry.connections.retain(|(n, _)| *n != number);
+                        }
+                    });
+                }
+            }
+        });
+        Ok(id)
+    }
+}
+
+/// Binds `path` so only this user can ever connect: the socket is created in a directory of its own
+/// that nobody else may enter, made private and only then moved into place.
+fn bind_private(path: &Path) -> Result<UnixListener> {
+    let Some(name) = path.file_name() else {
+        bail!("{} isn't a file path", path.display());
+    };
+    let mut hidden = std::ffi::OsString::from(".");
+    hidden.push(name);
+    hidden.push(format!(".{}", std::process::id()));
+    let directory = path.with_file_name(hidden);
+    fs::DirBuilder::new().mode(0o700).create(&directory)?;
+
+    let bound = directory.join("socket");
+    let listener = UnixListener::bind(&bound)
+        .and_then(|listener| fs::set_permissions(&bound, fs::Permissions::from_mode(0o600)).map(|_| listener))
+        .and_then(|listener| fs::rename(&bound, path).map(|_| listener));
+    if listener.is_err() {
+        let _ = fs::remove_file(&bound);
+    }
+    let _ = fs::remove_dir(&directory);
+    Ok(listener?)
+}
+
+/// Stops a server started with [`Server::start`], closing its connections and removing the socket.
+pub fn stop(id: i64) -> bool {
+    let running = {
+        let mut running = RUNNING.lock().unwrap();
+        let Some(index) = running.iter().position(|r| r.id == id) else {
+            return false;
+        };
+        running.remove(index)
+    };
+    close(running);
+    true
+}
+
+pub(crate) fn shutdown() {
+    let running = std::mem::take(&mut *RUNNING.lock().unwrap());
+    running.into_iter().for_each(close);
+}
+
+fn close(running: Running) {
+    for (_, connection) in running.connections {
+        let _ = connection.shutdown(std::net::Shutdown::Both);
+    }
+    // Wakes the accept loop so it sees it was stopped.
+    let _ = UnixStream::connect(&running.path);
+    let _ = fs::remove_file(&running.path);
+}
+
+/// Answers requests line by line until the client hangs up or the server is stopped.
+fn serve(stream: UnixStream) {
+    let Ok(reader) = stream.try_clone() else {
+        return;
+    };
+    let connection = Arc::new(Connection {
+        stream: Mutex::new(stream),
+        subscription: AtomicU64::new(0),
+    });
+
+    for line in BufReader::new(reader).lines() {
+        let Ok(line) = line else {
+            break;
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let response = match serde_json::from_str::<Value>(&line) {
+            Ok(Value::Array(batch)) if !batch.is_empty() => {
+                let responses: Vec<Value> = batch.into_iter().filter_map(|request| handle(&connection, request)).collect();
+                (!responses.is_empty()).then_some(Value::Array(responses))
+            }
+            Ok(request) => handle(&connection, request),
+            Err(e) => Some(error(Value::Null, PARSE_ERROR, &e.to_string())),
+        };
+
+        if let Some(response) = response {
+            if connection.send(&response).is_err() {
+                break;
+            }
+        }
+    }
+
+    // Ends the subscription with the next event.
+    connection.subscription.fetch_add(1, Ordering::SeqCst);
+}
+
+/// Runs one request. Returns no response for notifications, which carry no id.
+fn handle(connection: &Arc<Connection>, request: Value) -> Option<Value> {
+    let id = request.get("id").cloned();
+    let (Some("2.0"), Some(method)) = (request["jsonrpc"].as_str(), request["method"].as_str()) else {
+        return Some(error(id.unwrap_or(Value::Null), INVALID_REQUEST, "not a JSON-RPC 2.0 request"));
+    };
+    let params = &request["params"];
+
+    let result = match method {
+        "getMediaInfo" => json!(media::snapshot().media_info()),
+        "getPlaybackState" => json!(media::snapshot().playback_state()),
+        "getSnapshot" => {
+            let mut snapshot = json!(media::snapshot());
+            snapshot["generation"] = generation::current().0.into();
+            snapshot
+        }
+        "getGeneration" => json!(generation::current().0),
+        "getPlayers" => json!(media::players()),
+        "subscribe" => {
+            let window = Duration::from_millis(params["windowMs"].as_u64().unwrap_or(0));
+            connection.subscribe(window);
+            json!(true)
+        }
+        "unsubscribe" => {
+            connection.subscription.fetch_add(1, Ordering::SeqCst);
+            json!(true)
+        }
+        command => {
+            let value = params.get(0).or(params.get("value")).map(|v| v.as_str().map_or(v.to_string(), str::to_string));
+            match control::run(command, value.as_deref()) {
+                Ok(result) => json!(result),
+                Err(e @ control::Error::UnknownCommand(_)) => return id.map(|id| error(id, METHOD_NOT_FOUND, &e.to_string())),
+                Err(e) => return id.map(|id| error(id, INVALID_PARAMS, &e.to_string())),
+            }
+        }
+    };
+
+    id.map(|id| json!({ "jsonrpc": "2.0", "result": result, "id": id }))
+}
+
+fn error(id: Value, code: i64, message: &str) -> Value {
+    json!({ "jsonrpc": "2.0", "error": { "code": code, "message": message }, "id": id })
+}
+
+impl Connection {
+    fn send(&self, message: &Value) -> std::io::Result<()> {
+        let mut stream = self.stream.lock().unwrap();
+        writeln!(stream, "{}", message)
+    }
+
+    fn subscribe(self: &Arc<Self>, window: Duration) {
+        let subscription = self.subscription.fetch_add(1, Ordering::SeqCst) + 1;
+        let connection = self.clone();
+
+        event::subscribe_coalesced(window, move |event| {
+            connection.subscription.load(Ordering::SeqCst) == subscription
+                && connection.send(&json!({ "jsonrpc": "2.0", "method": "event", "params": event })).is_ok()
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{handle, Connection, INVALID_REQUEST, METHOD_NOT_FOUND},
+        serde_json::json,
+        std::{
+            os::unix::net::UnixStream,
+            sync::{atomic::AtomicU64, Arc, Mutex}
+        }
+    };
+
+    fn connection() -> Arc<Connection> {
+        let (stream, _) = UnixStream::pair().unwrap();
+        Arc::new(Connection {
+            stream: Mutex::new(stream),
+            subscription: AtomicU64::new(0),
+        })
+    }
+
+    #[test]
+    fn rejects_bad_requests() {
+        let connection = connection();
+
+        let response = handle(&connection, json!({ "jsonrpc": "1.0", "method": "play", "id": 1 })).unwrap();
+        assert_eq!(response["error"]["code"], INVALID_REQUEST);
+
+        let response = handle(&connection, json!({ "jsonrpc": "2.0", "method": "bogus", "id": 2 })).unwrap();
+        assert_eq!(response["error"]["code"], METHOD_NOT_FOUND);
+        assert_eq!(response["id"], 2);
+
+        // Notifications get no response, not even an error.
+        assert!(handle(&connection, json!({ "jsonrpc": "2.0", "method": "bogus" })).is_none());
+    }
+}
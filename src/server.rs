@@ -0,0 +1,271 @@
+//! An HTTP server for browser overlays and other devices on the network:
+//!
+//! - `GET /snapshot` returns the media info and playback state as of now, with the generation, as JSON.
+//! - `GET /art` returns the album art, if it is a local file.
+//! - `POST /control/{command}` runs a `NativeController` command, taking its argument from the
+//!   `value` query parameter, and returns the `CommandResult` as JSON.
+//! - `GET /events` upgrades to a WebSocket that pushes every event as JSON.
+//!
+//! With a token set, every request has to carry it as `Authorization: Bearer <token>`, or as the
+//! `token` query parameter since browsers can't set headers on WebSockets, and pages from any origin
+//! may read the responses. Listening anywhere but on loopback needs one. Without it, anything on the
+//! machine may connect but web pages may not: a request whose `Origin` isn't the server itself is
+//! refused, as is one whose `Host` is a domain name other than `localhost`, which keeps DNS rebinding
+//! out. Overlays loaded from a file or another site need a token.
+
+use {
+    crate::{
+        control,
+        event,
+        generation,
+        media,
+        CommandResult
+    },
+    anyhow::{anyhow, bail, Result},
+    serde_json::json,
+    std::{
+        fs,
+        io::{Cursor, Read},
+        net::{IpAddr, SocketAddr, ToSocketAddrs},
+        sync::{
+            atomic::{AtomicI64, Ordering},
+            mpsc,
+            Arc,
+            Mutex
+        },
+        time::Duration
+    },
+    tiny_http::{Header, Method, Request, Response},
+    tungstenite::{handshake::derive_accept_key, protocol::Role, Message, WebSocket}
+};
+
+/// Where to listen and who to let in. Built with [`Server::new`] and the setters.
+#[derive(Clone, Debug)]
+pub struct Server {
+    address: String,
+    token: Option<String>,
+    window: Duration,
+}
+
+static RUNNING: Mutex<Vec<(i64, Arc<tiny_http::Server>)>> = Mutex::new(Vec::new());
+static NEXT_ID: AtomicI64 = AtomicI64::new(1);
+
+impl Server {
+    /// `address` is a host and port, e.g. "127.0.0.1:8974", or "0.0.0.0:8974" for the whole network,
+    /// which needs a [`token`](Self::token).
+    pub fn new(address: &str) -> Self {
+        Self {
+            address: address.to_string(),
+            token: None,
+            window: Duration::from_millis(100),
+        }
+    }
+
+    pub fn token(mut self, token: &str) -> Self {
+        self.token = Some(token.to_string());
+        self
+    }
+
+    /// How long property changes are coalesced before WebSocket clients get a `Changed` event.
+    pub fn window(mut self, window: Duration) -> Self {
+        self.window = window;
+        self
+    }
+
+    /// Binds the address and serves on a background thread. Returns an id for [`stop`]. Fails without
+    /// a token unless the address is loopback only.
+    pub fn start(self) -> Result<i64> {
+        if self.token.is_none() {
+            let addresses: Vec<SocketAddr> = self.address.to_socket_addrs().map_err(|e| anyhow!("can't listen on {}: {}", self.address, e))?.collect();
+            if !addresses.iter().all(|a| a.ip().is_loopback()) {
+                bail!("{} isn't a loopback address, serving the network needs a token", self.address);
+            }
+        }
+
+        let server = Arc::new(tiny_http::Server::http(&self.address).map_err(|e| anyhow!("can't listen on {}: {}", self.address, e))?);
+        let id = NEXT_ID.fetch_add(1, Ordering::SeqCst);
+        RUNNING.lock().unwrap().push((id, server.clone()));
+
+        event::spawn(move || {
+            // Ends once `stop` unblocks the server.
+            for request in server.incoming_requests() {
+                self.handle(request);
+            }
+        });
+        Ok(id)
+    }
+
+    fn handle(&self, request: Request) {
+        let url = request.url().to_string();
+        let (path, query) = url.split_once('?').unwrap_or((&url, ""));
+
+        if self.token.is_none() && !same_origin(&request) {
+            let _ = request.respond(json(403, json!({ "error": "cross-origin requests need a token" })));
+            return;
+        }
+
+        if *request.method() == Method::Options {
+            let response = Response::empty(204)
+                .with_header(header("Access-Control-Allow-Methods", "GET, POST"))
+                .with_header(header("Access-Control-Allow-Headers", "Authorization"));
+            let _ = request.respond(self.cors(response));
+            return;
+        }
+
+        if let Some(token) = &self.token {
+            let bearer = request_header(&request, "Authorization").and_then(|h| h.strip_prefix("Bearer ").map(str::to_string));
+            if ![bearer, parameter(query, "token")].iter().flatten().any(|given| same_token(given, token)) {
+                let _ = request.respond(self.cors(json(401, json!({ "error": "unauthorized" }))));
+                return;
+            }
+        }
+
+        let response = match (request.method(), path) {
+            (Method::Get, "/snapshot") => snapshot(),
+            (Method::Get, "/art") => art(),
+            (Method::Post, path) if path.starts_with("/control/") => {
+                match control::run(&path["/control/".len()..], parameter(query, "value").as_deref()) {
+                    Ok(result) => json(status(&result), json!({ "result": result })),
+                    Err(e @ control::Error::UnknownCommand(_)) => json(404, json!({ "error": e.to_string() })),
+                    Err(e) => json(400, json!({ "error": e.to_string() })),
+                }
+            }
+            (Method::Get, "/events") => return self.events(request),
+            _ => json(404, json!({ "error": "not found" })),
+        };
+        let _ = request.respond(self.cors(response));
+    }
+
+    /// Lets pages from other origins read the response, which only a token makes safe.
+    fn cors<R: Read>(&self, response: Response<R>) -> Response<R> {
+        match self.token {
+            Some(_) => response.with_header(header("Access-Control-Allow-Origin", "*")),
+            None => response,
+        }
+    }
+
+    /// Upgrades to a WebSocket and pushes events to it on its own thread until the client goes away.
+    fn events(&self, request: Request) {
+        let Some(key) = request_header(&request, "Sec-WebSocket-Key") else {
+            let _ = request.respond(json(400, json!({ "error": "expected a WebSocket upgrade" })));
+            return;
+        };
+
+        let response = Response::empty(101).with_header(header("Sec-WebSocket-Accept", &derive_accept_key(key.as_bytes())));
+        let mut socket = WebSocket::from_raw_socket(request.upgrade("websocket", response), Role::Server, None);
+
+        let (sender, receiver) = mpsc::channel();
+        event::subscribe_coalesced(self.window, move |event| sender.send(event).is_ok());
+
+        event::spawn(move || {
+            for event in receiver {
+                let Ok(text) = serde_json::to_string(&event) else {
+                    continue;
+                };
+                if socket.send(Message::text(text)).is_err() {
+                    return;
+                }
+            }
+            let _ = socket.close(None);
+        });
+    }
+}
+
+/// Stops a server started with [`Server::start`]. WebSocket clients stay connected until they leave.
+pub fn stop(id: i64) -> bool {
+    let mut running = RUNNING.lock().unwrap();
+    let Some(index) = running.iter().position(|(i, _)| *i == id) else {
+        return false;
+    };
+    running.remove(index).1.unblock();
+    true
+}
+
+pub(crate) fn shutdown() {
+    for (_, server) in RUNNING.lock().unwrap().drain(..) {
+        server.unblock();
+    }
+}
+
+fn snapshot() -> Response<Cursor<Vec<u8>>> {
+    let (generation, _) = generation::current();
+    match serde_json::to_value(media::snapshot()) {
+        Ok(mut value) => {
+            value["generation"] = generation.into();
+            json(200, value)
+        }
+        Err(e) => json(500, json!({ "error": e.to_string() })),
+    }
+}
+
+fn art() -> Response<Cursor<Vec<u8>>> {
+    let Some(Ok(bytes)) = media::album_art_file(media::snapshot().media_info()).map(fs::read) else {
+        return json(404, json!({ "error": "no album art" }));
+    };
+
+    let content_type = match bytes.as_slice() {
+        [0x89, b'P', b'N', b'G', ..] => "image/png",
+        [0xFF, 0xD8, ..] => "image/jpeg",
+        [b'G', b'I', b'F', b'8', ..] => "image/gif",
+        [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'E', b'B', b'P', ..] => "image/webp",
+        _ => "application/octet-stream",
+    };
+    Response::from_data(bytes).with_header(header("Content-Type", content_type))
+}
+
+fn json(status: u16, value: serde_json::Value) -> Response<Cursor<Vec<u8>>> {
+    Response::from_string(value.to_string())
+        .with_status_code(status)
+        .with_header(header("Content-Type", "application/json"))
+}
+
+/// Whether `request` comes from a page served by this server, or from something that isn't a web
+/// page at all. Browsers always send `Origin` on cross-origin requests and WebSocket upgrades.
+fn same_origin(request: &Request) -> bool {
+    let Some(host) = request_header(request, "Host") else {
+        return false;
+    };
+
+    // A domain name resolving to this machine is what DNS rebinding looks like.
+    let name = host.rsplit_once(':').filter(|(_, port)| port.parse::<u16>().is_ok()).map_or(host.as_str(), |(name, _)| name);
+    let name = name.trim_start_matches('[').trim_end_matches(']');
+    if !name.eq_ignore_ascii_case("localhost") && name.parse::<IpAddr>().is_err() {
+        return false;
+    }
+
+    match request_header(request, "Origin") {
+        Some(origin) => origin.split_once("://").is_some_and(|(_, authority)| authority.eq_ignore_ascii_case(&host)),
+        None => true,
+    }
+}
+
+/// Takes as long wherever `given` differs, so the token can't be found one byte at a time.
+fn same_token(given: &str, token: &str) -> bool {
+    given.len() == token.len() && given.bytes().zip(token.bytes()).fold(0, |difference, (a, b)| difference | (a ^ b)) == 0
+}
+
+fn status(result: &CommandResult) -> u16 {
+    match result {
+        CommandResult::Ok => 200,
+        CommandResult::NotSupported => 501,
+        CommandResult::NoPlayer => 404,
+        CommandResult::CapabilityDisabled => 409,
+        CommandResult::Failed(_) => 500,
+    }
+}
+
+fn header(name: &str, value: &str) -> Header {
+    Header::from_bytes(name, value).unwrap()
+}
+
+fn request_header(request: &Request, name: &str) -> Option<String> {
+    request.headers().iter().find(|h| h.field.as_str().as_str().eq_ignore_ascii_case(name)).map(|h| h.value.to_string())
+}
+
+fn parameter(query: &str, name: &str) -> Option<String> {
+    query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, _)| *key == name)
+        .and_then(|(_, value)| media::percent_decode(&value.replace('+', " ")))
+}
@@ -0,0 +1,181 @@
+use {
+    crate::{
+        event::{self, Event},
+        media,
+        CommandResult,
+        Snapshot
+    },
+    futures_channel::{
+        mpsc::{self as channel, UnboundedReceiver},
+        oneshot
+    },
+    futures_core::Stream,
+    std::{
+        pin::Pin,
+        task::{Context, Poll},
+        thread,
+        time::Duration
+    }
+};
+
+/// Every event the Java listeners would get, in the same order: signal events as they arrive, and
+/// after each burst of property changes the typed `*Changed` events followed by `Changed`.
+pub struct Events {
+    receiver: UnboundedReceiver<Event>,
+}
+
+/// Only the `Changed` snapshots of [`Events`], starting with the current one.
+pub struct Snapshots {
+    events: Events,
+}
+
+/// Subscribes to player events. A burst of property changes arrives as one set of typed events and
+/// a `Changed` snapshot, sent `window` after the first change of the burst.
+pub fn events(window: Duration) -> Events {
+    let (sender, receiver) = channel::unbounded();
+    event::subscribe_coalesced(window, move |event| sender.unbounded_send(event).is_ok());
+    Events { receiver }
+}
+
+/// Subscribes to snapshots of the active player, each taken `window` after the first of a burst of property changes.
+pub fn snapshots(window: Duration) -> Snapshots {
+    Snapshots {
+        events: events(window),
+    }
+}
+
+impl Stream for Events {
+    type Item = Event;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Event>> {
+        Pin::new(&mut self.receiver).poll_next(cx)
+    }
+}
+
+impl Stream for Snapshots {
+    type Item = Snapshot;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Snapshot>> {
+        loop {
+            match Pin::new(&mut self.events).poll_next(cx) {
+                Poll::Ready(Some(Event::Changed(snapshot))) => return Poll::Ready(Some(snapshot)),
+                Poll::Ready(Some(_)) => continue,
+                Poll::Ready(None) => return Poll::Ready(None),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+/// Backend calls block, so each command runs on its own thread and the future only waits for its result.
+async fn run<F>(command: F) -> CommandResult
+where
+    F: FnOnce() -> CommandResult + Send + 'static,
+{
+    let (sender, receiver) = oneshot::channel();
+    thread::spawn(move || {
+        let _ = sender.send(command());
+    });
+
+    receiver.await.unwrap_or_else(|_| CommandResult::Failed("command thread panicked".to_string()))
+}
+
+pub async fn play() -> CommandResult {
+    run(media::play).await
+}
+
+pub async fn pause() -> CommandResult {
+    run(media::pause).await
+}
+
+pub async fn stop() -> CommandResult {
+    run(media::stop).await
+}
+
+pub async fn record() -> CommandResult {
+    run(media::record).await
+}
+
+pub async fn fast_forward() -> CommandResult {
+    run(media::fast_forward).await
+}
+
+pub async fn rewind() -> CommandResult {
+    run(media::rewind).await
+}
+
+pub async fn next() -> CommandResult {
+    run(media::next).await
+}
+
+pub async fn previous() -> CommandResult {
+    run(media::previous).await
+}
+
+pub async fn channel_up() -> CommandResult {
+    run(media::channel_up).await
+}
+
+pub async fn channel_down() -> CommandResult {
+    run(media::channel_down).await
+}
+
+pub async fn toggle_play_pause() -> CommandResult {
+    run(media::toggle_play_pause).await
+}
+
+pub async fn shuffle(shuffle: bool) -> CommandResult {
+    run(move || media::shuffle(shuffle)).await
+}
+
+pub async fn repeat() -> CommandResult {
+    run(media::repeat).await
+}
+
+pub async fn set_playback_rate(rate: f64) -> CommandResult {
+    run(move || media::set_playback_rate(rate)).await
+}
+
+pub async fn set_playback_position(position: i64) -> CommandResult {
+    run(move || media::set_playback_position(position)).await
+}
+
+pub async fn seek(seconds: f64) -> CommandResult {
+    run(move || media::seek(seconds)).await
+}
+
+pub async fn set_volume(volume: f64) -> CommandResult {
+    run(move || media::set_volume(volume)).await
+}
+
+pub async fn open_uri(uri: String) -> CommandResult {
+    run(move || media::open_uri(&uri)).await
+}
+
+pub async fn raise() -> CommandResult {
+    run(media::raise).await
+}
+
+pub async fn quit() -> CommandResult {
+    run(media::quit).await
+}
+
+pub async fn set_fullscreen(fullscreen: bool) -> CommandResult {
+    run(move || media::set_fullscreen(fullscreen)).await
+}
+
+pub async fn go_to(track_id: String) -> CommandResult {
+    run(move || media::go_to(&track_id)).await
+}
+
+pub async fn add_track(uri: String, after_track_id: Option<String>, set_as_current: bool) -> CommandResult {
+    run(move || media::add_track(&uri, after_track_id.as_deref(), set_as_current)).await
+}
+
+pub async fn remove_track(track_id: String) -> CommandResult {
+    run(move || media::remove_track(&track_id)).await
+}
+
+pub async fn activate_playlist(playlist_id: String) -> CommandResult {
+    run(move || media::activate_playlist(&playlist_id)).await
+}
@@ -0,0 +1,234 @@
+//! Now-playing lines like "Artist – Title (1:23/4:56)" from a template over the snapshot fields.
+//!
+//! `{{title}}` inserts a field, or nothing when the player doesn't report it. Filters follow a `|`:
+//! `duration` formats a time as `m:ss` or `h:mm:ss`, `truncate:N` shortens to N characters ending in
+//! `…`, `default:TEXT` stands in for a missing field and `markup` escapes `&`, `<`, `>` and `"`.
+//! `{{#artist}}…{{/artist}}` is rendered only when the field is present, `{{^artist}}…{{/artist}}`
+//! only when it is missing. A backslash makes the next character literal, so `\{{` prints `{{`.
+
+use {
+    crate::{media, Snapshot},
+    anyhow::{anyhow, bail, Result}
+};
+
+/// Every name a template can refer to.
+pub const FIELDS: [&str; 13] = [
+    "player", "title", "artist", "album", "albumArt", "status", "position", "length", "remaining", "volume", "shuffle", "repeat", "rate",
+];
+
+/// A parsed template, checked for unknown fields and filters and unbalanced sections.
+#[derive(Clone, Debug)]
+pub struct Template {
+    nodes: Vec<Node>,
+}
+
+#[derive(Clone, Debug)]
+enum Node {
+    Text(String),
+    Field(String, Vec<Filter>),
+    Section(String, bool, Vec<Node>),
+}
+
+#[derive(Clone, Debug)]
+enum Filter {
+    Duration,
+    Truncate(usize),
+    Default(String),
+    Markup,
+}
+
+impl Template {
+    pub fn parse(template: &str) -> Result<Self> {
+        // Sections being built, innermost last, with the nodes of their parent.
+        let mut open: Vec<(String, bool, Vec<Node>)> = Vec::new();
+        let mut nodes = Vec::new();
+        let mut text = String::new();
+        let mut rest = template;
+
+        while let Some(c) = rest.chars().next() {
+            if c == '\\' {
+                let escaped = rest[1..].chars().next().ok_or(anyhow!("template ends in a backslash"))?;
+                text.push(escaped);
+                rest = &rest[1 + escaped.len_utf8()..];
+                continue;
+            }
+            if !rest.starts_with("{{") {
+                text.push(c);
+                rest = &rest[c.len_utf8()..];
+                continue;
+            }
+
+            let end = rest.find("}}").ok_or(anyhow!("unclosed {{{{"))?;
+            let tag = rest[2..end].trim();
+            rest = &rest[end + 2..];
+
+            if !text.is_empty() {
+                nodes.push(Node::Text(std::mem::take(&mut text)));
+            }
+
+            if let Some(name) = tag.strip_prefix('#').or(tag.strip_prefix('^')) {
+                open.push((field(name)?, tag.starts_with('^'), std::mem::take(&mut nodes)));
+            } else if let Some(name) = tag.strip_prefix('/') {
+                let (field, inverted, parent) = open.pop().ok_or(anyhow!("{{{{/{}}}}} closes no section", name.trim()))?;
+                if field != name.trim() {
+                    bail!("{{{{/{}}}}} closes the section {}", name.trim(), field);
+                }
+                let section = std::mem::replace(&mut nodes, parent);
+                nodes.push(Node::Section(field, inverted, section));
+            } else {
+                let mut parts = tag.split('|');
+                let name = field(parts.next().unwrap_or_default())?;
+                nodes.push(Node::Field(name, parts.map(filter).collect::<Result<_>>()?));
+            }
+        }
+
+        if let Some((field, _, _)) = open.last() {
+            bail!("section {} is never closed", field);
+        }
+        if !text.is_empty() {
+            nodes.push(Node::Text(text));
+        }
+        Ok(Self { nodes })
+    }
+
+    /// Fills the template in from `snapshot`. `player` is what `{{player}}` stands for, if anything.
+    pub fn render(&self, snapshot: &Snapshot, player: Option<&str>) -> String {
+        let mut output = String::new();
+        render(&self.nodes, snapshot, player, &mut output);
+        output
+    }
+}
+
+fn field(name: &str) -> Result<String> {
+    let name = name.trim();
+    if !FIELDS.contains(&name) {
+        bail!("unknown field {}", name);
+    }
+    Ok(name.to_string())
+}
+
+fn filter(filter: &str) -> Result<Filter> {
+    let (name, argument) = filter.split_once(':').map_or((filter.trim(), None), |(n, a)| (n.trim(), Some(a)));
+    Ok(match (name, argument) {
+        ("duration", None) => Filter::Duration,
+        ("markup", None) => Filter::Markup,
+        ("truncate", Some(length)) => Filter::Truncate(length.trim().parse().map_err(|_| anyhow!("invalid length {}", length))?),
+        ("default", Some(text)) => Filter::Default(text.to_string()),
+        _ => bail!("unknown filter {}", filter),
+    })
+}
+
+fn render(nodes: &[Node], snapshot: &Snapshot, player: Option<&str>, output: &mut String) {
+    for node in nodes {
+        match node {
+            Node::Text(text) => output.push_str(text),
+            Node::Field(name, filters) => {
+                let value = filters.iter().fold(value(name, snapshot, player), apply);
+                output.push_str(&value.unwrap_or_default());
+            }
+            Node::Section(name, inverted, nodes) => {
+                if value(name, snapshot, player).is_some() != *inverted {
+                    render(nodes, snapshot, player, output);
+                }
+            }
+        }
+    }
+}
+
+/// The field as text, or `None` for what the platform reports as unavailable.
+fn value(name: &str, snapshot: &Snapshot, player: Option<&str>) -> Option<String> {
+    let (media_info, state) = (snapshot.media_info(), snapshot.playback_state());
+    let text = |text: &str| Some(text.to_string()).filter(|t| !t.is_empty() && t != "Unavailable");
+    let number = |n: i64| (n >= 0).then(|| n.to_string());
+
+    match name {
+        "player" => player.and_then(text),
+        "title" => text(media_info.title()),
+        "artist" => text(media_info.artist()),
+        "album" => text(media_info.album()),
+        "albumArt" => text(media_info.album_art()),
+        "status" => text(state.status()),
+        "position" => number(state.current_time()),
+        "length" => number(state.max_time()),
+        "remaining" => (state.current_time() >= 0).then(|| number(state.max_time() - state.current_time())).flatten(),
+        "volume" => (state.volume() >= 0.0).then(|| state.volume().to_string()),
+        "shuffle" => Some(state.is_shuffling().to_string()),
+        "repeat" => Some(state.repeat().to_string()),
+        "rate" => Some(state.playback_rate().to_string()),
+        _ => None,
+    }
+}
+
+fn apply(value: Option<String>, filter: &Filter) -> Option<String> {
+    match filter {
+        Filter::Default(text) => value.or(Some(text.clone())),
+        Filter::Duration => value.map(|v| v.parse().map_or(v, duration)),
+        Filter::Truncate(length) => value.map(|v| truncate(v, *length)),
+        Filter::Markup => value.map(|v| v.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")),
+    }
+}
+
+/// `m:ss`, or `h:mm:ss` from an hour on, for a time in the units of `currentTime`.
+fn duration(time: i64) -> String {
+    let seconds = time / media::time_units_per_second();
+    let (hours, minutes, seconds) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, seconds)
+    } else {
+        format!("{}:{:02}", minutes, seconds)
+    }
+}
+
+fn truncate(text: String, length: usize) -> String {
+    if text.chars().count() <= length {
+        return text;
+    }
+    let mut truncated: String = text.chars().take(length.saturating_sub(1)).collect();
+    if length > 0 {
+        truncated.push('…');
+    }
+    truncated
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::Template,
+        crate::{media, MediaInfo, PlaybackState, Snapshot}
+    };
+
+    fn snapshot(artist: &str) -> Snapshot {
+        let mut state = PlaybackState::empty();
+        state.is_playing = true;
+        state.current_time = 83 * media::time_units_per_second();
+        state.max_time = 296 * media::time_units_per_second();
+
+        let media_info = MediaInfo::new("A Song <Live>".to_string(), artist.to_string(), "Unavailable".to_string(), String::new());
+        Snapshot::new(media_info, state)
+    }
+
+    #[test]
+    fn renders_fields_and_sections() {
+        let template = Template::parse("{{#artist}}{{artist}} – {{/artist}}{{title}} ({{position|duration}}/{{length|duration}})").unwrap();
+
+        assert_eq!(template.render(&snapshot("Someone"), None), "Someone – A Song <Live> (1:23/4:56)");
+        assert_eq!(template.render(&snapshot("Unavailable"), None), "A Song <Live> (1:23/4:56)");
+    }
+
+    #[test]
+    fn applies_filters() {
+        let template = Template::parse("{{title|truncate:6|markup}} {{album|default:No album}}{{^album}}!{{/album}} \\{{player}}").unwrap();
+
+        assert_eq!(template.render(&snapshot("Someone"), None), "A Son… No album! {{player}}");
+    }
+
+    #[test]
+    fn rejects_invalid_templates() {
+        assert!(Template::parse("{{name}}").is_err());
+        assert!(Template::parse("{{title|upper}}").is_err());
+        assert!(Template::parse("{{#artist}}{{title}}").is_err());
+        assert!(Template::parse("{{#artist}}{{/title}}").is_err());
+        assert!(Template::parse("{{title").is_err());
+    }
+}
@@ -0,0 +1,183 @@
+use {
+    crate::{
+        event::{self, Event},
+        position,
+        MediaInfo,
+        Snapshot
+    },
+    std::{
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Condvar,
+            Mutex
+        },
+        time::Duration
+    }
+};
+
+/// Upper bound on a sleep, so interpolation drift is corrected against a fresh snapshot now and then.
+const RESYNC: Duration = Duration::from_secs(5);
+
+/// A seek that lands this close past a target still fires it.
+const SEEK_TOLERANCE: Duration = Duration::from_millis(250);
+
+/// Where a trigger fires, in the units of `PlaybackState.currentTime`: microseconds on Linux, 100 ns
+/// on Windows and seconds on macOS, see `media::time_units_per_second`.
+#[derive(Clone, Copy)]
+pub(crate) enum Kind {
+    At(i64),
+    BeforeEnd(i64),
+}
+
+struct Trigger {
+    id: i64,
+    kind: Kind,
+    /// Set once the position was seen before the target, so a trigger fires at most once per pass.
+    armed: bool,
+}
+
+/// Why the scheduler was woken before its sleep ran out.
+#[derive(Clone, Copy, PartialEq)]
+enum Wake {
+    Changed,
+    Seeked,
+}
+
+struct Triggers {
+    triggers: Vec<Trigger>,
+    next_id: i64,
+    wake: Option<Wake>,
+}
+
+static TRIGGERS: Mutex<Triggers> = Mutex::new(Triggers {
+    triggers: Vec::new(),
+    next_id: 1,
+    wake: None,
+});
+static WAKE: Condvar = Condvar::new();
+static SCHEDULER: AtomicBool = AtomicBool::new(false);
+
+impl Kind {
+    pub(crate) fn parse(kind: &str, time: i64) -> Option<Self> {
+        match kind {
+            "at" => Some(Kind::At(time)),
+            "beforeEnd" => Some(Kind::BeforeEnd(time)),
+            _ => None,
+        }
+    }
+
+    fn target(self, max_time: i64) -> Option<i64> {
+        match self {
+            Kind::At(time) => Some(time),
+            Kind::BeforeEnd(time) => (max_time > 0).then(|| (max_time - time).max(0)),
+        }
+    }
+}
+
+/// Registers a trigger and returns its id, which `PositionReached` events carry.
+pub(crate) fn add(kind: Kind) -> i64 {
+    if !SCHEDULER.swap(true, Ordering::SeqCst) {
+        start();
+    }
+
+    let mut triggers = TRIGGERS.lock().unwrap();
+    let id = triggers.next_id;
+    triggers.next_id += 1;
+    triggers.triggers.push(Trigger {
+        id,
+        kind,
+        armed: false,
+    });
+    wake(&mut triggers, Wake::Changed);
+    id
+}
+
+pub(crate) fn remove(id: i64) -> bool {
+    let mut triggers = TRIGGERS.lock().unwrap();
+    let before = triggers.triggers.len();
+    triggers.triggers.retain(|t| t.id != id);
+    triggers.triggers.len() != before
+}
+
+/// Drops every trigger and wakes the scheduler so it notices the shutdown.
+pub(crate) fn shutdown() {
+    SCHEDULER.store(false, Ordering::SeqCst);
+    let mut triggers = TRIGGERS.lock().unwrap();
+    triggers.triggers.clear();
+    wake(&mut triggers, Wake::Changed);
+}
+
+fn wake(triggers: &mut Triggers, reason: Wake) {
+    // A seek wins over a plain change, it decides whether skipped targets fire.
+    if triggers.wake != Some(Wake::Seeked) {
+        triggers.wake = Some(reason);
+    }
+    WAKE.notify_one();
+}
+
+fn start() {
+    // Seeks, rate changes and track changes all move the targets or the time left to reach them.
+    event::subscribe(|event| {
+        match event {
+            Event::Seeked(_) => wake(&mut TRIGGERS.lock().unwrap(), Wake::Seeked),
+            Event::PropertiesChanged => wake(&mut TRIGGERS.lock().unwrap(), Wake::Changed),
+            _ => {}
+        }
+        true
+    });
+
+    let epoch = event::epoch();
+    event::spawn(move || schedule(epoch));
+}
+
+fn schedule(epoch: u64) {
+    let mut track: Option<MediaInfo> = None;
+    let mut seeked = false;
+
+    while event::running(epoch) {
+        // Taking the snapshot also refreshes the interpolated position the sleep below is based on.
+        let snapshot = Snapshot::take();
+        let state = &snapshot.playback_state;
+        let current = state.current_time;
+        let track_changed = track.as_ref() != Some(&snapshot.media_info);
+        track = Some(snapshot.media_info.clone());
+
+        let mut fired = Vec::new();
+        let mut triggers = TRIGGERS.lock().unwrap();
+        for trigger in triggers.triggers.iter_mut() {
+            let target = trigger.kind.target(state.max_time);
+            if track_changed {
+                trigger.armed = false;
+            }
+
+            match target {
+                Some(target) if current < target => trigger.armed = true,
+                Some(target) if trigger.armed => {
+                    trigger.armed = false;
+                    // Jumping well past a target is a skip, not the position being reached.
+                    if !seeked || current - target <= position::units(SEEK_TOLERANCE) {
+                        fired.push(trigger.id);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let sleep = triggers.triggers
+            .iter()
+            .filter(|t| t.armed)
+            .filter_map(|t| t.kind.target(state.max_time))
+            .filter_map(position::until)
+            .min()
+            .map_or(RESYNC, |until| until.min(RESYNC));
+        drop(triggers);
+
+        for id in fired {
+            event::emit(Event::PositionReached(id, current));
+        }
+
+        let triggers = TRIGGERS.lock().unwrap();
+        let (mut triggers, _) = WAKE.wait_timeout_while(triggers, sleep, |t| t.wake.is_none()).unwrap();
+        seeked = triggers.wake.take() == Some(Wake::Seeked);
+    }
+}